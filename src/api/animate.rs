@@ -0,0 +1,31 @@
+use crate::error::Result;
+
+/// 图生视频参数
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    pub model: String,
+    /// 输出分辨率档位，如 480P、720P
+    pub resolution: String,
+    /// 片段时长（秒）
+    pub duration: u32,
+    /// 是否由模型改写扩展运动提示词
+    pub prompt_extend: bool,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            model: "wanx2.1-i2v-turbo".to_string(),
+            resolution: "720P".to_string(),
+            duration: 5,
+            prompt_extend: true,
+        }
+    }
+}
+
+/// 图生视频模型：把分镜图片按运动提示词生成一段短视频，其他服务商实现此 trait 即可接入
+#[allow(async_fn_in_trait)]
+pub trait ImageAnimator {
+    /// 生成视频保存到 `output_path`，返回校验后的实际路径
+    async fn animate(&self, image_path: &str, prompt: &str, output_path: &str) -> Result<String>;
+}
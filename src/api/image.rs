@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 万相 seed 的取值上限
+pub const MAX_SEED: u32 = 4_294_967_290;
+
+/// 一次文生图请求的完整参数，同时记录在 manifest.json 中以便复现
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageParams {
+    pub model: String,
+    /// 万相图片风格，如 <photography>、<anime>
+    pub style: String,
+    /// 图片尺寸，如 1280*720
+    pub size: String,
+    /// 每次生成的图片数量
+    pub n: u32,
+    /// 反向提示词
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    /// 随机种子，相同参数与种子可复现同一张图
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    /// 参考图 URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_img: Option<String>,
+    /// 参考图相似度 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_strength: Option<f64>,
+    /// 参考方式：repaint（参考内容）或 refonly（参考风格）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_mode: Option<String>,
+    /// 是否由模型改写扩展提示词，仅部分模型支持
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_extend: Option<bool>,
+}
+
+impl Default for ImageParams {
+    fn default() -> Self {
+        Self {
+            model: "wanx-v1".to_string(),
+            style: "<photography>".to_string(),
+            size: "1280*720".to_string(),
+            n: 1,
+            negative_prompt: None,
+            seed: None,
+            ref_img: None,
+            ref_strength: None,
+            ref_mode: None,
+            prompt_extend: None,
+        }
+    }
+}
+
+/// 单个分镜的图片参数覆盖，未设置的字段沿用全局配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_img: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_strength: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_extend: Option<bool>,
+}
+
+impl ImageParams {
+    /// 用分镜覆盖中已设置的字段替换当前参数
+    pub fn overlay(&self, overrides: &ImageOverrides) -> ImageParams {
+        ImageParams {
+            model: overrides
+                .model
+                .clone()
+                .unwrap_or_else(|| self.model.clone()),
+            style: overrides
+                .style
+                .clone()
+                .unwrap_or_else(|| self.style.clone()),
+            size: overrides.size.clone().unwrap_or_else(|| self.size.clone()),
+            n: overrides.n.unwrap_or(self.n),
+            negative_prompt: overrides
+                .negative_prompt
+                .clone()
+                .or_else(|| self.negative_prompt.clone()),
+            seed: overrides.seed.or(self.seed),
+            ref_img: overrides.ref_img.clone().or_else(|| self.ref_img.clone()),
+            ref_strength: overrides.ref_strength.or(self.ref_strength),
+            ref_mode: overrides.ref_mode.clone().or_else(|| self.ref_mode.clone()),
+            prompt_extend: overrides.prompt_extend.or(self.prompt_extend),
+        }
+    }
+
+    /// 是否支持参考图（目前只有 wanx-v1 接受 ref_img）
+    pub fn supports_ref_img(&self) -> bool {
+        self.model.starts_with("wanx-v1")
+    }
+
+    /// 未指定种子时在本地选取一个，使记录下来的参数可以原样复现
+    pub fn ensure_seed(&mut self) -> u32 {
+        *self.seed.get_or_insert_with(|| {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default();
+            (nanos % MAX_SEED as u128) as u32
+        })
+    }
+
+    /// 万相 image-synthesis 接口的请求体，未设置的可选参数不发送
+    pub fn request_body(&self, prompt: &str) -> Value {
+        let mut input = json!({ "prompt": prompt });
+        if let Some(negative_prompt) = &self.negative_prompt {
+            input["negative_prompt"] = json!(negative_prompt);
+        }
+        if let Some(ref_img) = &self.ref_img {
+            input["ref_img"] = json!(ref_img);
+        }
+
+        let mut parameters = json!({
+            "style": self.style,
+            "size": self.size,
+            "n": self.n.max(1),
+        });
+        if let Some(seed) = self.seed {
+            parameters["seed"] = json!(seed);
+        }
+        if let Some(ref_strength) = self.ref_strength {
+            parameters["ref_strength"] = json!(ref_strength);
+        }
+        if let Some(ref_mode) = &self.ref_mode {
+            parameters["ref_mode"] = json!(ref_mode);
+        }
+        if let Some(prompt_extend) = self.prompt_extend {
+            parameters["prompt_extend"] = json!(prompt_extend);
+        }
+
+        json!({
+            "model": self.model,
+            "input": input,
+            "parameters": parameters,
+        })
+    }
+}
@@ -1,3 +1,9 @@
+pub mod animate;
+pub mod image;
 pub mod qwen;
+pub mod task;
 
-pub use qwen::QwenClient;
+pub use animate::{AnimationOptions, ImageAnimator};
+pub use image::{ImageOverrides, ImageParams};
+pub use qwen::{QwenClient, QwenOptions};
+pub use task::{PollOptions, TaskClient};
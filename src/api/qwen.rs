@@ -1,77 +1,72 @@
+use crate::api::{AnimationOptions, ImageAnimator, ImageParams, PollOptions, TaskClient};
 use crate::error::{Result, VideoError};
-use crate::scene::Scene;
+use crate::media;
+use crate::progress::{Event, EventBus};
+use crate::scene::{Bible, BibleEntry, Line, Scene, SoundCue};
+use crate::speech::{self, Pacing, Voice, VoiceCatalogue};
+use base64::Engine;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::json;
+use std::path::Path;
 use std::time::Duration;
 use tracing::{info, warn};
 
 const QWEN_TEXT_API: &str = "https://dashscope.aliyuncs.com/api/v1/services/aigc/text-generation/generation";
 const QWEN_IMAGE_API: &str = "https://dashscope.aliyuncs.com/api/v1/services/aigc/text2image/image-synthesis";
+const QWEN_VIDEO_API: &str = "https://dashscope.aliyuncs.com/api/v1/services/aigc/video-generation/video-synthesis";
 const QWEN_TTS_API: &str = "https://dashscope.aliyuncs.com/api/v1/services/aigc/multimodal-generation/generation";
 
 #[derive(Debug, Clone)]
 pub struct QwenClient {
     api_key: String,
     client: Client,
+    options: QwenOptions,
+    tasks: TaskClient,
+    events: EventBus,
 }
 
-#[derive(Debug, Deserialize)]
-struct TextGenerationResponse {
-    output: TextOutput,
-    usage: Option<Usage>,
-}
-
-#[derive(Debug, Deserialize)]
-struct TextOutput {
-    text: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct Usage {
-    total_tokens: Option<u32>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ImageGenerationResponse {
-    output: ImageOutput,
-}
-
-#[derive(Debug, Deserialize)]
-struct ImageOutput {
-    task_id: String,
-    task_status: String,
+/// 千问接口使用的模型与轮询参数
+#[derive(Debug, Clone)]
+pub struct QwenOptions {
+    /// 生成分镜的文本模型
+    pub text_model: String,
+    /// 文生图默认参数，分镜可单独覆盖
+    pub image: ImageParams,
+    /// 图生视频参数
+    pub animation: AnimationOptions,
+    /// 异步任务（文生图、图生视频）的轮询参数
+    pub poll: PollOptions,
 }
 
-#[derive(Debug, Deserialize)]
-struct ImageTaskResponse {
-    output: ImageTaskOutput,
+impl Default for QwenOptions {
+    fn default() -> Self {
+        Self {
+            text_model: "qwen-plus".to_string(),
+            image: ImageParams::default(),
+            animation: AnimationOptions::default(),
+            poll: PollOptions::default(),
+        }
+    }
 }
 
+/// 文生图任务结果
 #[derive(Debug, Deserialize)]
 struct ImageTaskOutput {
-    task_status: String,
-    results: Option<Vec<ImageResult>>,
+    #[serde(default)]
+    results: Vec<ImageResult>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ImageResult {
-    url: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct TTSResponse {
-    output: TTSOutput,
+    /// 单张图片失败时没有 url，只有错误信息
+    url: Option<String>,
 }
 
+/// 图生视频任务结果
 #[derive(Debug, Deserialize)]
-struct TTSOutput {
-    audio: AudioResult,
-}
-
-#[derive(Debug, Deserialize)]
-struct AudioResult {
-    url: String,
+struct VideoTaskOutput {
+    video_url: Option<String>,
 }
 
 impl QwenClient {
@@ -81,30 +76,94 @@ impl QwenClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { api_key, client }
+        let options = QwenOptions::default();
+        let tasks = TaskClient::new(client.clone(), api_key.clone(), options.poll.clone());
+        Self {
+            api_key,
+            client,
+            options,
+            tasks,
+            events: EventBus::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: QwenOptions) -> Self {
+        self.tasks = TaskClient::new(
+            self.client.clone(),
+            self.api_key.clone(),
+            options.poll.clone(),
+        );
+        self.options = options;
+        self
+    }
+
+    /// 把语音分段进度发送到事件总线
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// 异步任务客户端，流程中断时用于取消尚未结束的远端任务
+    pub fn tasks(&self) -> &TaskClient {
+        &self.tasks
     }
 
     /// 使用千问大模型分析文本并生成分镜
-    pub async fn generate_scenes(&self, text: &str) -> Result<Vec<Scene>> {
+    ///
+    /// `sfx_tags` 为音效库中可用的标签，非空时让模型为分镜挑选环境声与音效。
+    /// 同时提取反复出现的角色与场景设定，用于保持各分镜画面一致。
+    /// `max_scenes` 限制分镜数量，模型仍可能超出，由调用方校验。
+    pub async fn generate_scenes(
+        &self,
+        text: &str,
+        sfx_tags: &[String],
+        max_scenes: Option<usize>,
+    ) -> Result<(Bible, Vec<Scene>)> {
         info!("Generating scenes from text using Qwen...");
 
+        let sound_hint = if sfx_tags.is_empty() {
+            String::new()
+        } else {
+            format!(
+                r#"如果场景适合加入环境声或音效，再加上 sounds 数组，每个元素包含：tag（只能从以下标签中选择：{}）、
+offset（相对场景开始的秒数）、volume（音量 dB，环境声建议 -20 左右，音效 -10 左右）、loop（环境声为 true，单次音效为 false）
+"#,
+                sfx_tags.join("、")
+            )
+        };
+
+        let limit_hint = match max_scenes {
+            Some(max) => format!("分镜数量不超过 {} 个，必要时合并相邻内容。\n", max),
+            None => String::new(),
+        };
+
         let prompt = format!(
             r#"请将以下文本分解为视频分镜脚本。每个分镜包含：
 1. 场景描述（用于生成图片的提示词，使用英文，详细描述画面内容）
 2. 对应的台词或字幕（保持原文）
 3. 该场景的建议时长（秒）
-
-请以JSON数组格式返回，每个元素包含：description（英文图片描述）、subtitle（中文字幕）、duration（数字）
-
+4. 如果该场景包含对话，按说话顺序拆分为台词列表，每句标明说话人（旁白不填说话人）
+5. 出场的角色与所在的场景
+
+先整理全文的设定集：characters 为反复出现的角色，settings 为反复出现的地点，每个元素包含 name（与原文一致的名字）、
+description（英文，固定的外貌或环境特征，如年龄、发型、服装、配色、建筑风格，不写动作）。
+分镜的 description 只描述画面内容和动作，不必重复设定中的外貌。
+
+请以JSON对象格式返回：{{"characters": [...], "settings": [...], "scenes": [...]}}。
+scenes 的每个元素包含：description（英文图片描述）、subtitle（中文字幕）、duration（数字）、
+characters（出场角色名数组）、setting（场景名，没有则省略）、
+motion（英文，画面中的动作与镜头运动，如 slow push in, leaves swaying in the wind），
+包含对话时再加上 lines 数组，每个元素包含：speaker（角色名，旁白省略）、text（该句原文）
+{}{}
 文本内容：
 {}
 
-直接返回JSON数组，不要其他说明文字。"#,
-            text
+直接返回JSON对象，不要其他说明文字。"#,
+            sound_hint, limit_hint, text
         );
 
         let request_body = json!({
-            "model": "qwen-plus",
+            "model": self.options.text_model,
             "input": {
                 "messages": [
                     {
@@ -129,7 +188,7 @@ impl QwenClient {
 
         if !response.status().is_success() {
             let error_text = response.text().await?;
-            return Err(VideoError::ApiError(format!(
+            return Err(VideoError::Api(format!(
                 "Qwen API error: {}",
                 error_text
             )));
@@ -140,7 +199,7 @@ impl QwenClient {
         // 提取生成的文本
         let generated_text = response_json["output"]["choices"][0]["message"]["content"]
             .as_str()
-            .ok_or_else(|| VideoError::ApiError("Failed to extract generated text".to_string()))?;
+            .ok_or_else(|| VideoError::Api("Failed to extract generated text".to_string()))?;
 
         info!("Generated scenes text: {}", generated_text);
 
@@ -152,154 +211,171 @@ impl QwenClient {
             .trim_end_matches("```")
             .trim();
 
+        #[derive(Deserialize)]
+        struct LineData {
+            speaker: Option<String>,
+            text: String,
+        }
+
         #[derive(Deserialize)]
         struct SceneData {
             description: String,
             subtitle: String,
             duration: f64,
+            #[serde(default)]
+            lines: Vec<LineData>,
+            #[serde(default)]
+            sounds: Vec<SoundCue>,
+            #[serde(default)]
+            characters: Vec<String>,
+            #[serde(default)]
+            setting: Option<String>,
+            #[serde(default)]
+            motion: Option<String>,
+        }
+
+        // 模型偶尔仍只返回分镜数组，此时没有设定集
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StoryboardData {
+            Full {
+                #[serde(default)]
+                characters: Vec<BibleEntry>,
+                #[serde(default)]
+                settings: Vec<BibleEntry>,
+                scenes: Vec<SceneData>,
+            },
+            ScenesOnly(Vec<SceneData>),
         }
 
-        let scene_data: Vec<SceneData> = serde_json::from_str(json_text)
-            .map_err(|e| VideoError::ApiError(format!("Failed to parse scenes JSON: {}", e)))?;
+        let data: StoryboardData = serde_json::from_str(json_text)
+            .map_err(|e| VideoError::Api(format!("Failed to parse scenes JSON: {}", e)))?;
+        let (mut bible, scene_data) = match data {
+            StoryboardData::Full {
+                characters,
+                settings,
+                scenes,
+            } => (
+                Bible {
+                    characters,
+                    settings,
+                },
+                scenes,
+            ),
+            StoryboardData::ScenesOnly(scenes) => (Bible::default(), scenes),
+        };
+        bible.assign_seeds();
 
         let scenes: Vec<Scene> = scene_data
             .into_iter()
             .enumerate()
-            .map(|(i, data)| Scene::new(i, data.description, data.subtitle, data.duration))
+            .map(|(i, data)| {
+                let mut scene = Scene::new(i, data.description, data.subtitle, data.duration);
+                scene.lines = data
+                    .lines
+                    .into_iter()
+                    .filter(|line| !line.text.trim().is_empty())
+                    .map(|line| {
+                        let speaker = line.speaker.filter(|s| !s.trim().is_empty());
+                        Line::new(speaker, line.text)
+                    })
+                    .collect();
+                // 只保留音效库中存在的标签，不采用模型给出的文件路径
+                scene.sounds = data
+                    .sounds
+                    .into_iter()
+                    .filter(|cue| {
+                        cue.tag
+                            .as_ref()
+                            .is_some_and(|tag| sfx_tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+                    })
+                    .map(|cue| SoundCue { file: None, ..cue })
+                    .collect();
+                scene.characters = data.characters;
+                scene.setting = data.setting.filter(|s| !s.trim().is_empty());
+                scene.motion = data.motion.filter(|s| !s.trim().is_empty());
+                scene
+            })
             .collect();
 
-        info!("Successfully generated {} scenes", scenes.len());
-        Ok(scenes)
+        info!(
+            "Successfully generated {} scenes, {} characters, {} settings",
+            scenes.len(),
+            bible.characters.len(),
+            bible.settings.len()
+        );
+        Ok((bible, scenes))
     }
 
-    /// 生成图片
-    pub async fn generate_image(&self, prompt: &str, output_path: &str) -> Result<()> {
+    /// 生成 `params.n` 张候选图片，依次保存为 `{output_stem}_{k}.png`（k 从 1 开始），
+    /// 返回校验并修正扩展名后的实际路径
+    pub async fn generate_images(
+        &self,
+        prompt: &str,
+        params: &ImageParams,
+        output_stem: &str,
+    ) -> Result<Vec<String>> {
         info!("Generating image for prompt: {}", prompt);
 
-        let request_body = json!({
-            "model": "wanx-v1",
-            "input": {
-                "prompt": prompt
-            },
-            "parameters": {
-                "style": "<photography>",
-                "size": "1280*720",
-                "n": 1
-            }
-        });
+        let request_body = params.request_body(prompt);
 
-        // 提交任务
-        let response = self
-            .client
-            .post(QWEN_IMAGE_API)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .header("X-DashScope-Async", "enable")
-            .json(&request_body)
-            .send()
+        let output: ImageTaskOutput = self
+            .tasks
+            .run(QWEN_IMAGE_API, &request_body, "Image generation")
             .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(VideoError::ApiError(format!(
-                "Image generation API error: {}",
-                error_text
-            )));
+        let image_urls: Vec<String> = output
+            .results
+            .into_iter()
+            .filter_map(|result| result.url)
+            .collect();
+        if image_urls.is_empty() {
+            return Err(VideoError::Api("No image URL in response".to_string()));
+        }
+        if image_urls.len() < params.n as usize {
+            warn!(
+                "Requested {} images but only {} were returned",
+                params.n,
+                image_urls.len()
+            );
         }
 
-        let task_response: ImageGenerationResponse = response.json().await?;
-        let task_id = task_response.output.task_id;
-
-        info!("Image generation task submitted: {}", task_id);
-
-        // 轮询任务状态
-        let image_url = self.wait_for_image_task(&task_id).await?;
+        let mut saved_paths = Vec::new();
+        for (k, image_url) in image_urls.iter().enumerate() {
+            // 下载图片
+            info!("Downloading image from: {}", image_url);
+            let image_data = self.client.get(image_url).send().await?.bytes().await?;
 
-        // 下载图片
-        info!("Downloading image from: {}", image_url);
-        let image_data = self.client.get(&image_url).send().await?.bytes().await?;
+            let output_path = format!("{}_{}.png", output_stem, k + 1);
+            tokio::fs::write(&output_path, image_data).await?;
 
-        tokio::fs::write(output_path, image_data).await?;
-        info!("Image saved to: {}", output_path);
+            // 校验图片可解码，并按真实格式修正扩展名
+            let saved_path = media::validate_image(&output_path).await?;
+            info!("Image saved to: {}", saved_path);
+            saved_paths.push(saved_path);
+        }
 
-        Ok(())
+        Ok(saved_paths)
     }
 
-    async fn wait_for_image_task(&self, task_id: &str) -> Result<String> {
-        // 千问图片生成任务查询 API
-        let query_url = "https://dashscope.aliyuncs.com/api/v1/tasks";
-        let max_retries = 60; // 最多等待5分钟
-        let retry_interval = Duration::from_secs(5);
-
-        for i in 0..max_retries {
-            tokio::time::sleep(retry_interval).await;
-
-            // 使用正确的任务查询 API
-            let get_url = format!("{}/{}", query_url, task_id);
-            
-            info!("Querying task status: {}", get_url);
-
-            let response = self
-                .client
-                .get(&get_url)
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .send()
-                .await?;
-
-            let status = response.status();
-            if !status.is_success() {
-                let error_text = response.text().await?;
-                warn!("Failed to get task status (HTTP {}): {}", status, error_text);
-                continue;
-            }
-
-            let response_text = response.text().await?;
-            info!("Task response: {}", response_text);
-            
-            let task_result: ImageTaskResponse = serde_json::from_str(&response_text)
-                .map_err(|e| VideoError::ApiError(format!("Failed to parse task response: {}", e)))?;
-
-            match task_result.output.task_status.as_str() {
-                "SUCCEEDED" => {
-                    if let Some(results) = task_result.output.results {
-                        if let Some(first_result) = results.first() {
-                            return Ok(first_result.url.clone());
-                        }
-                    }
-                    return Err(VideoError::ApiError("No image URL in response".to_string()));
-                }
-                "FAILED" => {
-                    return Err(VideoError::ApiError("Image generation failed".to_string()));
-                }
-                _ => {
-                    info!("Task status: {} (retry {}/{})", task_result.output.task_status, i + 1, max_retries);
-                }
-            }
-        }
-
-        Err(VideoError::ApiError("Image generation timeout".to_string()))
+    /// 当前 TTS 模型的音色目录
+    pub fn voice_catalogue(&self) -> &'static VoiceCatalogue {
+        &VoiceCatalogue::QWEN_TTS_FLASH
     }
 
-    /// 生成语音
-    pub async fn generate_speech(&self, text: &str, output_path: &str) -> Result<()> {
+    /// 生成语音，输出无损 WAV 到 `output_path`，返回其时长（秒）
+    pub async fn generate_speech(
+        &self,
+        text: &str,
+        output_path: &str,
+        voice: &Voice,
+        pacing: &Pacing,
+    ) -> Result<f64> {
         info!("Generating speech for text (length: {} chars)...", text.len());
+        let catalogue = self.voice_catalogue();
 
         // TTS API 限制：汉字按2个字符计算，最多600字符
-        // 保守起见，按最多250个汉字（500字符）来分段
-        const MAX_CHUNK_SIZE: usize = 250;
-        
-        let chars: Vec<char> = text.chars().collect();
-        let total_chars = chars.len();
-        
-        let mut chunks = Vec::new();
-        let mut start = 0;
-        
-        while start < total_chars {
-            let end = std::cmp::min(start + MAX_CHUNK_SIZE, total_chars);
-            let chunk: String = chars[start..end].iter().collect();
-            chunks.push(chunk);
-            start = end;
-        }
+        // 按句子/分句切分，只有单句超长时才硬切
+        let chunks = speech::split_for_tts(text, &catalogue.chunk_limits);
 
         if chunks.len() > 1 {
             info!("Text too long, splitting into {} chunks", chunks.len());
@@ -312,23 +388,19 @@ impl QwenClient {
 
         // 为每个分段生成音频
         for (i, chunk) in chunks.iter().enumerate() {
-            let chunk_file = if chunks.len() == 1 {
-                output_path.to_string()
-            } else {
-                format!("{}.part{}.mp3", output_path, i)
-            };
-
             info!("Generating speech chunk {}/{} ({} chars)", i + 1, chunks.len(), chunk.chars().count());
             
+            // 语速、音调、音量及非原生采样率在合成后用 FFmpeg 处理
             let request_body = json!({
-                "model": "qwen3-tts-flash",
+                "model": catalogue.model,
                 "input": {
                     "text": chunk
                 },
                 "parameters": {
-                    "voice": "Cherry",
+                    "voice": voice.voice,
+                    "language_type": voice.language,
                     "format": "wav",       // API 实际返回 WAV
-                    "sample_rate": 24000
+                    "sample_rate": voice.native_sample_rate
                 }
             });
 
@@ -343,7 +415,7 @@ impl QwenClient {
 
             if !response.status().is_success() {
                 let error_text = response.text().await?;
-                return Err(VideoError::ApiError(format!(
+                return Err(VideoError::Api(format!(
                     "TTS API error (chunk {} length {}): {}",
                     i + 1, chunk.chars().count(), error_text
                 )));
@@ -355,7 +427,7 @@ impl QwenClient {
             // qwen3-tts-flash 返回的结构是 output.audio.url
             let audio_url = response_json["output"]["audio"]["url"]
                 .as_str()
-                .ok_or_else(|| VideoError::ApiError(format!(
+                .ok_or_else(|| VideoError::Api(format!(
                     "No audio URL in response. Full response: {}", 
                     serde_json::to_string(&response_json).unwrap_or_default()
                 )))?;
@@ -363,65 +435,134 @@ impl QwenClient {
             // 下载音频文件
             info!("Downloading audio chunk from: {}", audio_url);
             let audio_data = self.client.get(audio_url).send().await?.bytes().await?;
+
+            // 按返回内容的实际格式保存分段，不做任何转码
+            let extension = media::sniff_audio_extension(&audio_data).unwrap_or("bin");
+            let chunk_file = format!("{}.part{}.{}", output_path, i, extension);
             tokio::fs::write(&chunk_file, audio_data).await?;
-            
+
+            let (chunk_file, _) = media::validate_audio(&chunk_file).await?;
+            let chunk_file = match pacing.trim_threshold {
+                Some(threshold) => trim_chunk(&chunk_file, threshold).await?,
+                None => chunk_file,
+            };
+
+            // 分段边缘静音已裁掉，上一分段在句末结束时补上句间停顿；
+            // 单句超长在分句或硬切处断开时不加停顿，避免句中出现长顿
+            if i > 0 && pacing.sentence_pause > 0.0 && speech::ends_sentence(&chunks[i - 1]) {
+                let pause_file = format!("{}.pause{}.wav", output_path, i);
+                media::generate_silence(pacing.sentence_pause, voice.native_sample_rate, &pause_file)
+                    .await?;
+                audio_files.push(pause_file);
+            }
             audio_files.push(chunk_file);
+            self.events.emit(Event::SpeechChunkDone {
+                file: output_path.to_string(),
+                chunk: i + 1,
+                chunks: chunks.len(),
+            });
         }
 
-        // 如果有多个分段，需要合并
-        if audio_files.len() > 1 {
+        // 单个 WAV 分段直接作为母带，其余情况无损合并为 PCM WAV
+        if audio_files.len() == 1 && audio_files[0].ends_with(".wav") {
+            tokio::fs::rename(&audio_files[0], output_path).await?;
+        } else {
             info!("Merging {} audio chunks...", audio_files.len());
-            self.merge_audio_files(&audio_files, output_path).await?;
-            
+            media::concat_audio(&audio_files, output_path).await?;
+
             // 删除临时文件
             for file in audio_files {
                 tokio::fs::remove_file(file).await.ok();
             }
         }
 
-        info!("Speech saved to: {}", output_path);
-        Ok(())
+        if let Some(filter) = voice.post_filter() {
+            info!("Applying voice adjustments: {}", filter);
+            let raw_path = format!("{}.raw.wav", output_path);
+            tokio::fs::rename(output_path, &raw_path).await?;
+            media::filter_audio(&raw_path, &filter, output_path).await?;
+            tokio::fs::remove_file(&raw_path).await.ok();
+        }
+
+        let (_, duration) = media::validate_audio(output_path).await?;
+        info!("Speech saved to: {} ({:.2}s)", output_path, duration);
+        Ok(duration)
     }
+}
 
-    /// 合并多个音频文件
-    async fn merge_audio_files(&self, files: &[String], output: &str) -> Result<()> {
-        use std::process::Command;
-        use std::path::PathBuf;
-        
-        // 创建 FFmpeg concat 列表文件
-        let concat_list = format!("{}.concat.txt", output);
-        let mut content = String::new();
-        for file in files {
-            // 转换为绝对路径
-            let abs_path = PathBuf::from(file)
-                .canonicalize()
-                .map_err(|e| VideoError::ApiError(format!("Failed to get absolute path for {}: {}", file, e)))?;
-            content.push_str(&format!("file '{}'\n", abs_path.display()));
-        }
-        tokio::fs::write(&concat_list, content).await?;
-
-        // 使用 FFmpeg 合并音频，并转换为 MP3
-        let output_cmd = Command::new("ffmpeg")
-            .args([
-                "-y",
-                "-f", "concat",
-                "-safe", "0",
-                "-i", &concat_list,
-                "-c:a", "libmp3lame",  // 使用 MP3 编码器
-                "-b:a", "192k",        // 比特率
-                output,
-            ])
-            .output()
-            .map_err(|e| VideoError::ApiError(format!("Failed to merge audio: {}", e)))?;
-
-        if !output_cmd.status.success() {
-            let error = String::from_utf8_lossy(&output_cmd.stderr);
-            return Err(VideoError::ApiError(format!("FFmpeg merge failed: {}", error)));
+impl ImageAnimator for QwenClient {
+    /// 万相图生视频：图片以 Base64 data URL 提交，无需先上传
+    async fn animate(&self, image_path: &str, prompt: &str, output_path: &str) -> Result<String> {
+        info!("Animating {} with prompt: {}", image_path, prompt);
+        let options = &self.options.animation;
+
+        let image_data = tokio::fs::read(image_path).await?;
+        let mime = match Path::new(image_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("webp") => "image/webp",
+            Some("bmp") => "image/bmp",
+            _ => "image/png",
+        };
+        let img_url = format!(
+            "data:{};base64,{}",
+            mime,
+            base64::engine::general_purpose::STANDARD.encode(image_data)
+        );
+
+        let request_body = json!({
+            "model": options.model,
+            "input": {
+                "prompt": prompt,
+                "img_url": img_url
+            },
+            "parameters": {
+                "resolution": options.resolution,
+                "duration": options.duration,
+                "prompt_extend": options.prompt_extend
+            }
+        });
+
+        let output: VideoTaskOutput = self
+            .tasks
+            .run(QWEN_VIDEO_API, &request_body, "Video generation")
+            .await?;
+        let video_url = output
+            .video_url
+            .ok_or_else(|| VideoError::Api("No video URL in response".to_string()))?;
+        info!("Downloading video from: {}", video_url);
+        let video_data = self.client.get(&video_url).send().await?.bytes().await?;
+        tokio::fs::write(output_path, video_data).await?;
+
+        let info = media::probe(output_path).await?;
+        if info.video.is_none() || info.duration.is_none() {
+            return Err(VideoError::Media(format!(
+                "{} is not a playable video (format: {})",
+                output_path, info.format_name
+            )));
         }
+        info!("Video saved to: {}", output_path);
+        Ok(output_path.to_string())
+    }
+}
 
-        // 删除临时列表文件
-        tokio::fs::remove_file(&concat_list).await.ok();
-        
-        Ok(())
+/// 裁剪分段首尾静音；整段几乎无声导致裁剪结果不可用时保留原文件
+async fn trim_chunk(chunk_file: &str, threshold: f64) -> Result<String> {
+    let trimmed = format!("{}.trim.wav", chunk_file);
+    media::trim_silence(chunk_file, threshold, &trimmed).await?;
+    match media::validate_audio(&trimmed).await {
+        Ok((trimmed, _)) => {
+            tokio::fs::remove_file(chunk_file).await.ok();
+            Ok(trimmed)
+        }
+        Err(e) => {
+            warn!("Keeping untrimmed chunk {}: {}", chunk_file, e);
+            tokio::fs::remove_file(&trimmed).await.ok();
+            Ok(chunk_file.to_string())
+        }
     }
 }
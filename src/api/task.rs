@@ -0,0 +1,564 @@
+use crate::error::{Result, VideoError};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// DashScope 异步任务查询接口
+const DASHSCOPE_TASKS_API: &str = "https://dashscope.aliyuncs.com/api/v1/tasks";
+
+/// 异步任务的轮询参数：间隔从 `interval` 开始按 `backoff` 倍增长，最长 `max_interval`，
+/// 自提交起超过 `timeout` 仍未结束即取消任务并报错
+#[derive(Debug, Clone)]
+pub struct PollOptions {
+    pub interval: Duration,
+    pub max_interval: Duration,
+    pub backoff: f64,
+    pub timeout: Duration,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            max_interval: Duration::from_secs(30),
+            backoff: 1.5,
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl PollOptions {
+    fn next_interval(&self, current: Duration) -> Duration {
+        current
+            .mul_f64(self.backoff.max(1.0))
+            .min(self.max_interval.max(self.interval))
+    }
+}
+
+/// DashScope 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+    #[serde(other)]
+    Unknown,
+}
+
+/// 提交与查询响应中 `output` 的公共字段，任务结果字段由调用方按类型解析
+#[derive(Debug, Deserialize)]
+struct TaskState {
+    #[serde(default)]
+    task_id: Option<String>,
+    task_status: TaskStatus,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskEnvelope {
+    output: Value,
+}
+
+/// 接口出错时返回的错误体
+#[derive(Debug, Default, Deserialize)]
+struct ApiErrorBody {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// 通用的 DashScope 异步任务客户端：提交任务、按退避间隔轮询并解析结果
+///
+/// 已提交但尚未结束的任务记录在 `pending` 中，流程被中断时调用 `cancel_pending` 取消远端任务。
+#[derive(Debug, Clone)]
+pub struct TaskClient {
+    client: Client,
+    api_key: String,
+    options: PollOptions,
+    pending: Arc<Mutex<BTreeSet<String>>>,
+    tasks_api: String,
+}
+
+impl TaskClient {
+    pub fn new(client: Client, api_key: String, options: PollOptions) -> Self {
+        Self {
+            client,
+            api_key,
+            options,
+            pending: Arc::default(),
+            tasks_api: DASHSCOPE_TASKS_API.to_string(),
+        }
+    }
+
+    /// 替换任务查询接口地址，测试时指向本地接收方
+    #[cfg(test)]
+    fn with_tasks_api(mut self, url: impl Into<String>) -> Self {
+        self.tasks_api = url.into();
+        self
+    }
+
+    /// 提交任务并等待完成，`label` 用于日志与错误信息（如 "Image generation"）
+    pub async fn run<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &Value,
+        label: &str,
+    ) -> Result<T> {
+        let task_id = self.submit(url, body, label).await?;
+        self.wait(&task_id, label).await
+    }
+
+    /// 以异步模式提交任务，返回任务 ID
+    pub async fn submit(&self, url: &str, body: &Value, label: &str) -> Result<String> {
+        let response = self
+            .client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .header("X-DashScope-Async", "enable")
+            .json(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(VideoError::Api(format!(
+                "{} API error (HTTP {}): {}",
+                label,
+                status,
+                describe_error(&text)
+            )));
+        }
+
+        let state: TaskState = serde_json::from_str::<TaskEnvelope>(&text)
+            .and_then(|envelope| serde_json::from_value(envelope.output))
+            .map_err(|e| {
+                VideoError::Api(format!("Failed to parse {} task response: {}", label, e))
+            })?;
+        let task_id = state.task_id.ok_or_else(|| {
+            VideoError::Api(format!("{} response has no task ID: {}", label, text))
+        })?;
+
+        info!(
+            "{} task submitted: {} ({:?})",
+            label, task_id, state.task_status
+        );
+        self.pending.lock().unwrap().insert(task_id.clone());
+        Ok(task_id)
+    }
+
+    /// 轮询任务直到结束，成功时把 `output` 解析为 `T`
+    ///
+    /// 网络错误、限流、服务端错误与无法识别的状态视为暂时性的，继续轮询直到超时；
+    /// 任务失败时返回服务端给出的错误码与信息，超时或其他原因提前放弃时取消远端任务。
+    pub async fn wait<T: DeserializeOwned>(&self, task_id: &str, label: &str) -> Result<T> {
+        let result = self.poll(task_id, label).await;
+        self.pending.lock().unwrap().remove(task_id);
+        result
+    }
+
+    async fn poll<T: DeserializeOwned>(&self, task_id: &str, label: &str) -> Result<T> {
+        let url = format!("{}/{}", self.tasks_api, task_id);
+        let deadline = Instant::now() + self.options.timeout;
+        let mut interval = self.options.interval;
+        let mut polls = 0;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                warn!("{} task {} timed out, cancelling", label, task_id);
+                let error = VideoError::Api(format!(
+                    "{} timed out after {:.0}s (task {})",
+                    label,
+                    self.options.timeout.as_secs_f64(),
+                    task_id
+                ));
+                return self.abandon(task_id, error).await;
+            }
+            tokio::time::sleep(interval.min(remaining)).await;
+            interval = self.options.next_interval(interval);
+            polls += 1;
+
+            let response = match self
+                .client
+                .get(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    warn!("Failed to query task {}: {}", task_id, e);
+                    continue;
+                }
+            };
+
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            if !status.is_success() {
+                if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                    warn!(
+                        "Failed to get task status (HTTP {}): {}",
+                        status,
+                        describe_error(&text)
+                    );
+                    continue;
+                }
+                let error = VideoError::Api(format!(
+                    "{} task {} query failed (HTTP {}): {}",
+                    label,
+                    task_id,
+                    status,
+                    describe_error(&text)
+                ));
+                return self.abandon(task_id, error).await;
+            }
+
+            let parsed = serde_json::from_str::<TaskEnvelope>(&text).and_then(|envelope| {
+                let state = serde_json::from_value::<TaskState>(envelope.output.clone())?;
+                Ok((envelope.output, state))
+            });
+            let (output, state) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    let error =
+                        VideoError::Api(format!("Failed to parse {} task status: {}", label, e));
+                    return self.abandon(task_id, error).await;
+                }
+            };
+
+            match state.task_status {
+                TaskStatus::Succeeded => {
+                    info!("{} task {} succeeded", label, task_id);
+                    return serde_json::from_value(output).map_err(|e| {
+                        VideoError::Api(format!("Failed to parse {} task result: {}", label, e))
+                    });
+                }
+                TaskStatus::Failed | TaskStatus::Canceled => {
+                    return Err(VideoError::Api(format!(
+                        "{} task {} {:?}: [{}] {}",
+                        label,
+                        task_id,
+                        state.task_status,
+                        state.code.as_deref().unwrap_or("unknown"),
+                        state.message.as_deref().unwrap_or("no message")
+                    )));
+                }
+                TaskStatus::Unknown => {
+                    warn!(
+                        "{} task {} has an unrecognised status, polling again in {:.1}s",
+                        label,
+                        task_id,
+                        interval.as_secs_f64()
+                    );
+                }
+                TaskStatus::Pending | TaskStatus::Running => {
+                    info!(
+                        "{} task {}: {:?} (poll {}, next in {:.1}s)",
+                        label,
+                        task_id,
+                        state.task_status,
+                        polls,
+                        interval.as_secs_f64()
+                    );
+                }
+            }
+        }
+    }
+
+    /// 提前放弃轮询：先取消远端任务再返回错误，避免任务在后台继续运行计费
+    async fn abandon<T>(&self, task_id: &str, error: VideoError) -> Result<T> {
+        if let Err(e) = self.cancel(task_id).await {
+            warn!("Failed to cancel task {}: {}", task_id, e);
+        }
+        Err(error)
+    }
+
+    /// 取消远端任务（DashScope 只能取消仍在排队的任务）
+    pub async fn cancel(&self, task_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(format!("{}/{}/cancel", self.tasks_api, task_id))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(VideoError::Api(format!(
+                "Failed to cancel task {} (HTTP {}): {}",
+                task_id,
+                status,
+                describe_error(&text)
+            )));
+        }
+        info!("Cancelled task {}", task_id);
+        Ok(())
+    }
+
+    /// 取消所有已提交但尚未结束的任务，流程中断时调用
+    pub async fn cancel_pending(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for task_id in pending {
+            if let Err(e) = self.cancel(&task_id).await {
+                warn!("{}", e);
+            }
+        }
+    }
+}
+
+/// 从错误体中提取错误码与信息，无法解析时原样返回
+fn describe_error(text: &str) -> String {
+    match serde_json::from_str::<ApiErrorBody>(text) {
+        Ok(ApiErrorBody {
+            code: Some(code),
+            message,
+        }) => format!("[{}] {}", code, message.unwrap_or_default()),
+        _ => text.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn options(interval_ms: u64, timeout_ms: u64) -> PollOptions {
+        PollOptions {
+            interval: Duration::from_millis(interval_ms),
+            max_interval: Duration::from_millis(interval_ms * 2),
+            backoff: 1.5,
+            timeout: Duration::from_millis(timeout_ms),
+        }
+    }
+
+    /// 在 127.0.0.1 上模拟任务接口：提交返回任务 t1，查询依次以 `polls` 中的状态码与 output 响应
+    /// （用完后一直返回最后一个），取消总是成功；返回接口地址与收到的请求（方法 路径）
+    async fn dashscope(polls: Vec<(u16, Value)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            let mut served = 0;
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let line = read_request_line(&mut stream).await;
+                log.lock().unwrap().push(line.clone());
+                let (status, body) = if line.ends_with("/cancel") {
+                    (200, json!({ "output": {} }))
+                } else if line.starts_with("POST") {
+                    (
+                        200,
+                        json!({ "output": { "task_id": "t1", "task_status": "PENDING" } }),
+                    )
+                } else {
+                    served += 1;
+                    let (status, output) = &polls[(served - 1).min(polls.len() - 1)];
+                    (*status, json!({ "output": output }))
+                };
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {} Test\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.ok();
+            }
+        });
+        (base, requests)
+    }
+
+    /// 读完请求头与请求体，返回 "方法 路径"
+    async fn read_request_line(stream: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let header_end = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            assert!(n > 0, "connection closed before headers");
+        };
+        let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+        let length: usize = head
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .unwrap_or(0);
+        while data.len() < header_end + length {
+            let n = stream.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed before body");
+            data.extend_from_slice(&buf[..n]);
+        }
+        let mut parts = head.split_whitespace();
+        format!(
+            "{} {}",
+            parts.next().unwrap_or_default(),
+            parts.next().unwrap_or_default()
+        )
+    }
+
+    fn client(base: &str, options: PollOptions) -> TaskClient {
+        TaskClient::new(Client::new(), "sk-test".to_string(), options)
+            .with_tasks_api(format!("{}/api/v1/tasks", base))
+    }
+
+    fn status(status: &str) -> Value {
+        json!({ "task_id": "t1", "task_status": status })
+    }
+
+    fn cancelled(requests: &Mutex<Vec<String>>) -> bool {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|line| line == "POST /api/v1/tasks/t1/cancel")
+    }
+
+    #[test]
+    fn interval_grows_up_to_the_maximum() {
+        let options = options(100, 1000);
+        let mut interval = options.interval;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            interval = options.next_interval(interval);
+            seen.push(interval.as_millis());
+        }
+        assert_eq!(seen, [150, 200, 200, 200]);
+
+        // 退避倍数小于 1 时不缩短，最大间隔小于初始间隔时以初始间隔为准
+        let odd = PollOptions {
+            backoff: 0.5,
+            max_interval: Duration::from_millis(10),
+            ..options
+        };
+        assert_eq!(
+            odd.next_interval(Duration::from_millis(100)),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn error_body_is_described_by_code_and_message() {
+        assert_eq!(
+            describe_error(r#"{"code": "Throttling", "message": "Too many requests"}"#),
+            "[Throttling] Too many requests"
+        );
+        assert_eq!(describe_error("  bad gateway \n"), "bad gateway");
+    }
+
+    #[tokio::test]
+    async fn transient_errors_and_unknown_status_keep_polling() {
+        let mut done = status("SUCCEEDED");
+        done["results"] = json!([{ "url": "https://example.com/a.png" }]);
+        let (base, requests) = dashscope(vec![
+            (429, json!({})),
+            (500, json!({})),
+            (200, status("SUSPENDED")),
+            (200, status("RUNNING")),
+            (200, done),
+        ])
+        .await;
+        let tasks = client(&base, options(5, 5000));
+
+        let output: Value = tasks
+            .run(&format!("{}/submit", base), &json!({}), "Image generation")
+            .await
+            .unwrap();
+        assert_eq!(output["results"][0]["url"], "https://example.com/a.png");
+        assert_eq!(requests.lock().unwrap().len(), 6);
+        assert!(!cancelled(&requests));
+        assert!(tasks.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_task_reports_the_server_error() {
+        let mut failed = status("FAILED");
+        failed["code"] = json!("DataInspectionFailed");
+        failed["message"] = json!("Input data may contain inappropriate content.");
+        let (base, requests) = dashscope(vec![(200, failed)]).await;
+        let tasks = client(&base, options(5, 5000));
+
+        let error = tasks
+            .run::<Value>(&format!("{}/submit", base), &json!({}), "Image generation")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Failed"), "{}", error);
+        assert!(error.contains("[DataInspectionFailed] Input data may contain"));
+        assert!(!cancelled(&requests));
+    }
+
+    #[tokio::test]
+    async fn timeout_cancels_the_remote_task_without_overshooting() {
+        let (base, requests) = dashscope(vec![(200, status("RUNNING"))]).await;
+        // 轮询间隔远大于总时限，最后一次等待按剩余时间截断
+        let tasks = client(
+            &base,
+            PollOptions {
+                interval: Duration::from_secs(30),
+                ..options(30_000, 200)
+            },
+        );
+
+        let started = Instant::now();
+        let error = tasks
+            .run::<Value>(&format!("{}/submit", base), &json!({}), "Video generation")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(error.contains("timed out"), "{}", error);
+        assert!(cancelled(&requests));
+    }
+
+    #[tokio::test]
+    async fn client_errors_cancel_the_remote_task() {
+        let (base, requests) = dashscope(vec![(400, json!({}))]).await;
+        let tasks = client(&base, options(5, 5000));
+
+        let result = tasks
+            .run::<Value>(&format!("{}/submit", base), &json!({}), "Image generation")
+            .await;
+        assert!(result.is_err());
+        assert!(cancelled(&requests));
+    }
+
+    #[tokio::test]
+    async fn cancel_pending_cancels_submitted_tasks() {
+        let (base, requests) = dashscope(vec![(200, status("RUNNING"))]).await;
+        let tasks = client(&base, options(5, 5000));
+
+        let task_id = tasks
+            .submit(&format!("{}/submit", base), &json!({}), "Image generation")
+            .await
+            .unwrap();
+        assert_eq!(task_id, "t1");
+        tasks.cancel_pending().await;
+
+        assert!(cancelled(&requests));
+        assert!(tasks.pending.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,554 @@
+use crate::config::{presets, Settings};
+use crate::error::{Result, VideoError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// 项目目录（当前目录）与用户配置目录中的配置文件名
+pub const CONFIG_FILE: &str = "auto-video.toml";
+
+/// 环境变量前缀：AUTO_VIDEO_<SECTION>_<KEY>，如 AUTO_VIDEO_VOICE_RATE=1.2
+pub const ENV_PREFIX: &str = "AUTO_VIDEO_";
+
+/// 选择预设的环境变量
+pub const PRESET_ENV: &str = "AUTO_VIDEO_PRESET";
+
+/// 配置项的来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Preset(String),
+    Env(String),
+    Cli(String),
+    Job(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Preset(name) => write!(f, "preset {}", name),
+            Source::Env(var) => write!(f, "env {}", var),
+            Source::Cli(flag) => write!(f, "cli {}", flag),
+            Source::Job(id) => write!(f, "job {}", id),
+        }
+    }
+}
+
+/// 命令行中显式给出的配置项
+#[derive(Debug, Clone, Default)]
+pub struct CliOverrides {
+    entries: Vec<(String, String, Value)>,
+}
+
+impl CliOverrides {
+    /// 记录 `key`（如 voice.rate）的命令行取值，None 表示未指定
+    pub fn set<T: Serialize>(&mut self, key: impl Into<String>, flag: &str, value: Option<T>) {
+        if let Some(value) = value.and_then(|v| Value::try_from(v).ok()) {
+            self.entries.push((key.into(), flag.to_string(), value));
+        }
+    }
+}
+
+/// 合并后的配置，以及每一项的来源
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub settings: Settings,
+    values: Table,
+    sources: BTreeMap<String, Source>,
+    /// 合并前的配置文件、环境变量与命令行，任务选用预设时按同样的顺序重新合并
+    inputs: Inputs,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Inputs {
+    files: Vec<(PathBuf, Table)>,
+    env: Vec<(String, String)>,
+    cli: CliOverrides,
+}
+
+impl LoadedConfig {
+    /// 依次合并：内置默认值 → 预设 → 用户配置目录 → 项目目录 → --config → 环境变量 → 命令行
+    ///
+    /// 预设紧贴在默认值之上，配置文件中显式写出的值总是优先于预设。
+    pub fn load(explicit: Option<&str>, cli: CliOverrides) -> Result<Self> {
+        let user_file = dirs::config_dir().map(|dir| dir.join("auto-video").join(CONFIG_FILE));
+        let mut files = Vec::new();
+        for path in user_file.into_iter().chain([PathBuf::from(CONFIG_FILE)]) {
+            if path.is_file() {
+                let table = read_file(&path)?;
+                files.push((path, table));
+            }
+        }
+        if let Some(path) = explicit {
+            let path = PathBuf::from(path);
+            let table = read_file(&path)?;
+            files.push((path, table));
+        }
+
+        let mut env: Vec<(String, String)> = std::env::vars().collect();
+        env.sort();
+        Self::assemble(files, env, cli)
+    }
+
+    /// 按固定顺序合并已读取的配置文件、环境变量与命令行
+    pub(crate) fn assemble(
+        files: Vec<(PathBuf, Table)>,
+        env: Vec<(String, String)>,
+        cli: CliOverrides,
+    ) -> Result<Self> {
+        Self::assemble_with_preset(Inputs { files, env, cli }, None)
+    }
+
+    /// `preset` 给出时替代命令行、环境变量与配置文件中选用的预设
+    fn assemble_with_preset(inputs: Inputs, preset: Option<Value>) -> Result<Self> {
+        let Inputs { files, env, cli } = inputs.clone();
+        let defaults = match Value::try_from(Settings::default()) {
+            Ok(Value::Table(table)) => table,
+            _ => Table::new(),
+        };
+
+        // 先合并配置文件得到其中选用的预设名与自定义预设，再按正式顺序重新合并
+        let mut scratch = Self::empty();
+        scratch.merge(defaults.clone(), &Source::Default);
+        for (path, table) in &files {
+            scratch.merge(table.clone(), &Source::File(path.clone()));
+        }
+
+        // 预设名可来自命令行、环境变量或配置文件，优先级与普通配置项相同
+        let preset = preset.or_else(|| {
+            cli.entries
+                .iter()
+                .find(|(key, _, _)| key == "preset")
+                .map(|(_, _, value)| value.clone())
+                .or_else(|| {
+                    env.iter()
+                        .find(|(var, _)| var == PRESET_ENV)
+                        .map(|(_, raw)| Value::String(raw.clone()))
+                })
+                .or_else(|| scratch.values.get("preset").cloned())
+        });
+
+        let mut loaded = Self::empty();
+        loaded.merge(defaults, &Source::Default);
+        if let Some(name) = preset {
+            let name = name
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| VideoError::Config("preset must be a string".to_string()))?;
+            let table = scratch.preset(&name)?;
+            loaded.merge(table, &Source::Preset(name));
+        }
+        for (path, table) in files {
+            loaded.merge(table, &Source::File(path));
+        }
+
+        for (var, raw) in env {
+            let key = if var == "DASHSCOPE_API_KEY" {
+                "api.key".to_string()
+            } else if var == PRESET_ENV {
+                "preset".to_string()
+            } else if let Some((section, key)) = var
+                .strip_prefix(ENV_PREFIX)
+                .and_then(|rest| rest.split_once('_'))
+            {
+                let section = section.to_ascii_lowercase();
+                // 只接受已知分区，避免误用其他程序的同前缀变量
+                if !loaded.values.contains_key(&section) {
+                    continue;
+                }
+                format!("{}.{}", section, key.to_ascii_lowercase())
+            } else {
+                continue;
+            };
+            loaded.set(&key, parse_env_value(&raw), &Source::Env(var));
+        }
+
+        for (key, flag, value) in cli.entries {
+            loaded.set(&key, value, &Source::Cli(flag));
+        }
+
+        loaded.settings = Value::Table(loaded.values.clone())
+            .try_into()
+            .map_err(|e| VideoError::Config(format!("{}", e)))?;
+        loaded.inputs = inputs;
+        Ok(loaded)
+    }
+
+    fn empty() -> Self {
+        Self {
+            settings: Settings::default(),
+            values: Table::new(),
+            sources: BTreeMap::new(),
+            inputs: Inputs::default(),
+        }
+    }
+
+    /// 在当前配置之上再叠加一层（如服务模式下单个任务的选项）
+    ///
+    /// 层中用 `preset` 选择的预设替换原来选用的预设，仍紧贴在默认值之上，配置文件、环境变量与
+    /// 命令行中显式写出的值继续优先；该层其余配置项最后合并。
+    pub fn layered(&self, mut layer: Table, source: Source) -> Result<LoadedConfig> {
+        layer.remove("presets");
+        let mut loaded = match layer.get("preset") {
+            Some(name) => Self::assemble_with_preset(self.inputs.clone(), Some(name.clone()))?,
+            None => LoadedConfig {
+                settings: self.settings.clone(),
+                values: self.values.clone(),
+                sources: self.sources.clone(),
+                inputs: self.inputs.clone(),
+            },
+        };
+        loaded.merge(layer, &source);
+
+        loaded.settings = Value::Table(loaded.values.clone())
+            .try_into()
+            .map_err(|e| VideoError::Config(format!("{}", e)))?;
+        Ok(loaded)
+    }
+
+    /// 查找预设：配置文件中的同名预设优先于内置预设
+    fn preset(&self, name: &str) -> Result<Table> {
+        if let Some(Value::Table(table)) = self
+            .values
+            .get("presets")
+            .and_then(|presets| presets.get(name))
+        {
+            return Ok(table.clone());
+        }
+        if let Some(preset) = presets::builtin(name) {
+            return toml::from_str(preset.settings).map_err(|e| {
+                VideoError::Config(format!("Invalid built-in preset {}: {}", name, e))
+            });
+        }
+        Err(VideoError::Config(format!(
+            "Unknown preset '{}', available: {}",
+            name,
+            self.preset_names().join(", ")
+        )))
+    }
+
+    /// 全部可用预设名：内置预设在前，随后是配置文件中定义的预设
+    fn preset_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = presets::BUILTIN_PRESETS
+            .iter()
+            .map(|preset| preset.name.to_string())
+            .collect();
+        if let Some(Value::Table(user)) = self.values.get("presets") {
+            for name in user.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// 合并一层配置，记录每个叶子项的来源
+    fn merge(&mut self, layer: Table, source: &Source) {
+        for (key, value) in flatten(&layer, "") {
+            // 预设不能再嵌套选择或定义预设
+            if matches!(source, Source::Preset(_))
+                && (key == "preset" || key.starts_with("presets"))
+            {
+                continue;
+            }
+            self.set(&key, value, source);
+        }
+    }
+
+    fn set(&mut self, key: &str, value: Value, source: &Source) {
+        let (section, name) = match key.split_once('.') {
+            Some((section, name)) => (section, Some(name)),
+            None => (key, None),
+        };
+        match name {
+            Some(name) => {
+                let table = self
+                    .values
+                    .entry(section.to_string())
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if !table.is_table() {
+                    *table = Value::Table(Table::new());
+                }
+                if let Value::Table(table) = table {
+                    table.insert(name.to_string(), value);
+                }
+            }
+            None => {
+                // 空分区不覆盖已有的同名分区
+                let empty = matches!(&value, Value::Table(table) if table.is_empty());
+                if empty && self.values.contains_key(section) {
+                    return;
+                }
+                self.values.insert(section.to_string(), value);
+            }
+        }
+        self.sources.insert(key.to_string(), source.clone());
+    }
+
+    /// 列出可用预设，标记当前选用的预设
+    pub fn print_presets(&self) {
+        let current = self.settings.preset.as_deref();
+        for name in self.preset_names() {
+            let marker = if current == Some(name.as_str()) {
+                "*"
+            } else {
+                " "
+            };
+            let user = matches!(
+                self.values
+                    .get("presets")
+                    .and_then(|presets| presets.get(&name)),
+                Some(Value::Table(_))
+            );
+            let description = match presets::builtin(&name) {
+                Some(_) if user => "user-defined (overrides built-in)",
+                Some(preset) => preset.description,
+                None => "user-defined",
+            };
+            println!("{} {:<16} {}", marker, name, description);
+        }
+    }
+
+    /// 打印生效的配置及来源，API Key、回调密钥与租户令牌只显示末四位
+    pub fn print(&self) {
+        for (key, value) in flatten(&self.values, "") {
+            let shown = match (key.as_str(), &value) {
+                ("api.key" | "server.webhook_secret", Value::String(secret)) => {
+                    Value::String(mask(secret)).to_string()
+                }
+                (key, Value::Table(tenant)) if key.starts_with("tenants.") => {
+                    let mut tenant = tenant.clone();
+                    for field in ["token", "api_key"] {
+                        if let Some(Value::String(secret)) = tenant.get_mut(field) {
+                            *secret = mask(secret);
+                        }
+                    }
+                    Value::Table(tenant).to_string()
+                }
+                _ => value.to_string(),
+            };
+            let source = self
+                .sources
+                .get(&key)
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+            println!("{:<28} = {:<40} # {}", key, shown, source);
+        }
+    }
+}
+
+/// 只保留末四位
+fn mask(secret: &str) -> String {
+    let tail = secret
+        .char_indices()
+        .rev()
+        .nth(3)
+        .map_or(secret, |(i, _)| &secret[i..]);
+    format!("****{}", tail)
+}
+
+/// 展开为 `section.key` → 值；空表保留为一项以便显示
+fn flatten(table: &Table, prefix: &str) -> Vec<(String, Value)> {
+    let mut leaves = Vec::new();
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(inner) if prefix.is_empty() && !inner.is_empty() => {
+                leaves.extend(flatten(inner, &path));
+            }
+            _ => leaves.push((path, value.clone())),
+        }
+    }
+    leaves
+}
+
+fn read_file(path: &Path) -> Result<Table> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| VideoError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    toml::from_str(&content)
+        .map_err(|e| VideoError::Config(format!("Invalid config {}: {}", path.display(), e)))
+}
+
+/// 环境变量按 TOML 字面量解析（数字、布尔值），否则视为字符串
+fn parse_env_value(raw: &str) -> Value {
+    format!("value = {}", raw)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, content: &str) -> (PathBuf, Table) {
+        (PathBuf::from(name), content.parse().unwrap())
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(var, raw)| (var.to_string(), raw.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn file_values_override_the_preset() {
+        let files = vec![file(
+            "auto-video.toml",
+            "preset = \"anime\"\n[voice]\nrate = 0.9\n",
+        )];
+        let loaded = LoadedConfig::assemble(files, Vec::new(), CliOverrides::default()).unwrap();
+
+        assert_eq!(loaded.settings.voice.rate, Some(0.9));
+        assert_eq!(
+            loaded.sources["voice.rate"],
+            Source::File(PathBuf::from("auto-video.toml"))
+        );
+        // 文件未写的项仍来自预设
+        assert_eq!(loaded.settings.voice.voice.as_deref(), Some("Cherry"));
+        assert_eq!(
+            loaded.sources["voice.voice"],
+            Source::Preset("anime".to_string())
+        );
+        assert_eq!(loaded.sources["project.output"], Source::Default);
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let files = vec![
+            file("user.toml", "[voice]\nrate = 0.8\nvoice = \"Ethan\"\n"),
+            file("project.toml", "[voice]\nrate = 0.9\n"),
+        ];
+        let vars = env(&[
+            ("AUTO_VIDEO_VOICE_RATE", "1.1"),
+            ("AUTO_VIDEO_NOPE_KEY", "x"),
+        ]);
+        let mut cli = CliOverrides::default();
+        cli.set("voice.voice", "--voice", Some("Ryan"));
+        let loaded = LoadedConfig::assemble(files, vars, cli).unwrap();
+
+        assert_eq!(loaded.settings.voice.rate, Some(1.1));
+        assert_eq!(
+            loaded.sources["voice.rate"],
+            Source::Env("AUTO_VIDEO_VOICE_RATE".to_string())
+        );
+        assert_eq!(loaded.settings.voice.voice.as_deref(), Some("Ryan"));
+        assert_eq!(
+            loaded.sources["voice.voice"],
+            Source::Cli("--voice".to_string())
+        );
+        // 未知分区的环境变量被忽略
+        assert!(!loaded.values.contains_key("nope"));
+    }
+
+    #[test]
+    fn preset_from_cli_stays_below_files() {
+        let files = vec![file("auto-video.toml", "[voice]\nrate = 0.9\n")];
+        let mut cli = CliOverrides::default();
+        cli.set("preset", "--preset", Some("storybook"));
+        let loaded = LoadedConfig::assemble(files, Vec::new(), cli).unwrap();
+
+        assert_eq!(loaded.settings.preset.as_deref(), Some("storybook"));
+        assert_eq!(loaded.settings.voice.rate, Some(0.9));
+    }
+
+    #[test]
+    fn user_presets_replace_builtin_ones() {
+        let files = vec![file(
+            "auto-video.toml",
+            "[presets.anime]\nvoice = { voice = \"Ethan\" }\n",
+        )];
+        let loaded = LoadedConfig::assemble(
+            files,
+            env(&[(PRESET_ENV, "anime")]),
+            CliOverrides::default(),
+        )
+        .unwrap();
+
+        assert_eq!(loaded.settings.voice.voice.as_deref(), Some("Ethan"));
+        assert_eq!(loaded.settings.voice.rate, None);
+        assert_eq!(
+            loaded.sources["preset"],
+            Source::Env(PRESET_ENV.to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_preset_is_an_error() {
+        let mut cli = CliOverrides::default();
+        cli.set("preset", "--preset", Some("noir"));
+        assert!(LoadedConfig::assemble(Vec::new(), Vec::new(), cli).is_err());
+    }
+
+    #[test]
+    fn job_layer_records_its_source() {
+        let base = LoadedConfig::assemble(
+            vec![file("auto-video.toml", "[voice]\nrate = 0.9\n")],
+            Vec::new(),
+            CliOverrides::default(),
+        )
+        .unwrap();
+        let layer: Table = "[voice]\npitch = 1.2\n".parse().unwrap();
+        let job = base.layered(layer, Source::Job("abc".to_string())).unwrap();
+
+        assert_eq!(job.settings.voice.pitch, Some(1.2));
+        assert_eq!(job.sources["voice.pitch"], Source::Job("abc".to_string()));
+        assert_eq!(
+            job.sources["voice.rate"],
+            Source::File(PathBuf::from("auto-video.toml"))
+        );
+    }
+
+    #[test]
+    fn job_preset_stays_below_files_and_replaces_the_server_preset() {
+        let base = LoadedConfig::assemble(
+            vec![file(
+                "auto-video.toml",
+                "preset = \"storybook\"\n[voice]\nrate = 0.9\n",
+            )],
+            Vec::new(),
+            CliOverrides::default(),
+        )
+        .unwrap();
+        let layer: Table = "preset = \"anime\"\n[voice]\npitch = 1.2\n"
+            .parse()
+            .unwrap();
+        let job = base.layered(layer, Source::Job("abc".to_string())).unwrap();
+
+        assert_eq!(job.settings.preset.as_deref(), Some("anime"));
+        assert_eq!(job.sources["preset"], Source::Job("abc".to_string()));
+        // 配置文件的值仍优先于任务选用的预设
+        assert_eq!(job.settings.voice.rate, Some(0.9));
+        assert_eq!(
+            job.sources["voice.rate"],
+            Source::File(PathBuf::from("auto-video.toml"))
+        );
+        assert_eq!(
+            job.sources["voice.voice"],
+            Source::Preset("anime".to_string())
+        );
+        assert_eq!(job.sources["voice.pitch"], Source::Job("abc".to_string()));
+        // 服务端原来选用的预设不再残留
+        assert!(!job
+            .sources
+            .values()
+            .any(|source| *source == Source::Preset("storybook".to_string())));
+    }
+
+    #[test]
+    fn mask_keeps_the_last_four_characters() {
+        assert_eq!(mask("sk-1234567890"), "****7890");
+        assert_eq!(mask("abc"), "****abc");
+    }
+}
@@ -0,0 +1,432 @@
+pub mod layers;
+pub mod presets;
+
+pub use layers::{CliOverrides, LoadedConfig};
+
+use crate::api::{AnimationOptions, ImageParams, PollOptions, QwenOptions};
+use crate::media::{AudioFormat, ImageFit, LoudnessPreset, LoudnessTarget};
+use crate::speech::{Pacing, VoiceSettings};
+use crate::video::{MusicOptions, SubtitleOptions, SubtitleStyle, Transition};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use toml::Table;
+
+/// auto-video.toml 的完整配置，各层合并后的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// 选用的风格预设
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// 用户自定义预设：名称 → 与本文件相同结构的配置片段
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub presets: BTreeMap<String, Table>,
+    pub project: ProjectSettings,
+    pub api: ApiSettings,
+    pub image: ImageSettings,
+    pub voice: VoiceSettings,
+    /// 对话角色 → 音色
+    pub cast: BTreeMap<String, String>,
+    pub speech: SpeechSettings,
+    pub subtitle: SubtitleSettings,
+    pub video: VideoSettings,
+    pub animation: AnimationSettings,
+    pub audio: AudioSettings,
+    pub server: ServerSettings,
+    /// 服务模式的租户：名称 → 访问令牌、DashScope Key 与配额；为空时接口不校验令牌
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tenants: BTreeMap<String, TenantSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectSettings {
+    /// 输出视频路径
+    pub output: String,
+    /// 工作目录
+    pub work_dir: String,
+    /// 旁白音频格式
+    pub narration_format: AudioFormat,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            output: "output.mp4".to_string(),
+            work_dir: "./output".to_string(),
+            narration_format: AudioFormat::Wav,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiSettings {
+    /// DashScope API Key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub text_model: String,
+    pub image_model: String,
+    /// 异步任务首次轮询间隔（秒），之后按 poll_backoff 倍增长
+    pub poll_interval: f64,
+    /// 轮询间隔上限（秒）
+    pub poll_max_interval: f64,
+    pub poll_backoff: f64,
+    /// 单个任务的最长等待时间（秒），超时后取消任务
+    pub poll_timeout: f64,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        let qwen = QwenOptions::default();
+        Self {
+            key: None,
+            text_model: qwen.text_model,
+            image_model: qwen.image.model,
+            poll_interval: qwen.poll.interval.as_secs_f64(),
+            poll_max_interval: qwen.poll.max_interval.as_secs_f64(),
+            poll_backoff: qwen.poll.backoff,
+            poll_timeout: qwen.poll.timeout.as_secs_f64(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImageSettings {
+    /// 万相图片风格
+    pub style: String,
+    /// 反向提示词
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    /// 图片尺寸，同时决定成片画面尺寸
+    pub size: String,
+    /// 图片与画面尺寸不一致时的适配方式
+    pub fit: ImageFit,
+    /// 每个分镜生成的图片数量
+    pub n: u32,
+    /// 随机种子，不设置时每张图随机选取并记录在 manifest.json 中
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    /// 参考图 URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_img: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_strength: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_mode: Option<String>,
+    /// 提示词智能改写
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_extend: Option<bool>,
+    /// 候选图片评分命令，不设置时按分辨率与曝光自动选择
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_command: Option<String>,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        let image = ImageParams::default();
+        Self {
+            style: image.style,
+            negative_prompt: image.negative_prompt,
+            size: image.size,
+            fit: ImageFit::Blur,
+            n: image.n,
+            seed: image.seed,
+            ref_img: image.ref_img,
+            ref_strength: image.ref_strength,
+            ref_mode: image.ref_mode,
+            prompt_extend: image.prompt_extend,
+            score_command: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpeechSettings {
+    /// 发音词典路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lexicon: Option<String>,
+    /// 数字、日期、金额朗读规范化
+    pub normalize: bool,
+    /// 裁剪语音首尾静音
+    pub trim_silence: bool,
+    pub silence_threshold: f64,
+    pub sentence_pause: f64,
+    pub scene_pause: f64,
+    pub lead_in: f64,
+    pub tail: f64,
+}
+
+impl Default for SpeechSettings {
+    fn default() -> Self {
+        let pacing = Pacing::default();
+        Self {
+            lexicon: None,
+            normalize: true,
+            trim_silence: pacing.trim_threshold.is_some(),
+            silence_threshold: pacing.trim_threshold.unwrap_or(-50.0),
+            sentence_pause: pacing.sentence_pause,
+            scene_pause: pacing.scene_pause,
+            lead_in: pacing.lead_in,
+            tail: pacing.tail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubtitleSettings {
+    pub style: SubtitleStyle,
+    pub highlight_color: String,
+    pub font_file: String,
+    pub font_name: String,
+    pub font_size: u32,
+}
+
+impl Default for SubtitleSettings {
+    fn default() -> Self {
+        let subtitles = SubtitleOptions::default();
+        Self {
+            style: subtitles.style,
+            highlight_color: subtitles.highlight_color,
+            font_file: subtitles.font_file,
+            font_name: subtitles.font_name,
+            font_size: subtitles.font_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VideoSettings {
+    pub fps: u32,
+    /// 本地图片/视频素材目录，分镜素材按标签匹配其中的文件
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_dir: Option<String>,
+    pub transition: Transition,
+    /// 转场时长（秒）
+    pub transition_duration: f64,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            fps: 30,
+            media_dir: None,
+            transition: Transition::Cut,
+            transition_duration: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationSettings {
+    /// 用图生视频模型让分镜图片动起来，失败时退回 Ken Burns 效果
+    pub enabled: bool,
+    pub model: String,
+    pub resolution: String,
+    /// 每段视频的时长（秒）
+    pub duration: u32,
+    pub prompt_extend: bool,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        let animation = AnimationOptions::default();
+        Self {
+            enabled: false,
+            model: animation.model,
+            resolution: animation.resolution,
+            duration: animation.duration,
+            prompt_extend: animation.prompt_extend,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioSettings {
+    /// 背景音乐文件或目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music: Option<String>,
+    pub music_volume: f64,
+    pub duck_db: f64,
+    pub music_fade: f64,
+    /// 音效库目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sfx_dir: Option<String>,
+    pub loudness: LoudnessPreset,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_lufs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub true_peak: Option<f64>,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            music: None,
+            music_volume: -18.0,
+            duck_db: -12.0,
+            music_fade: 2.0,
+            sfx_dir: None,
+            loudness: LoudnessPreset::Web,
+            target_lufs: None,
+            true_peak: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    /// `auto-video serve` 监听地址
+    pub bind: String,
+    /// 同时运行的任务数
+    pub workers: usize,
+    /// 排队任务上限，队列满时拒绝新任务
+    pub queue_size: usize,
+    /// 任务目录，每个任务使用其中独立的子目录
+    pub jobs_dir: String,
+    /// 任务回调的 HMAC-SHA256 签名密钥，未设置时不接受 callback_url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// 回调最多投递次数
+    pub webhook_attempts: u32,
+    /// 回调首次重试间隔（秒），之后每次翻倍
+    pub webhook_retry_interval: f64,
+    /// 单次投递的超时（秒）
+    pub webhook_timeout: f64,
+    /// 允许回调投递到回环、私有网段等非公网地址，仅用于内网部署或本地调试
+    pub allow_private_callbacks: bool,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:8080".to_string(),
+            workers: 2,
+            queue_size: 32,
+            jobs_dir: "./jobs".to_string(),
+            webhook_secret: None,
+            webhook_attempts: 5,
+            webhook_retry_interval: 5.0,
+            webhook_timeout: 10.0,
+            allow_private_callbacks: false,
+        }
+    }
+}
+
+/// 服务模式的租户，未设置的配额不限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantSettings {
+    /// 调用接口的访问令牌（`Authorization: Bearer <token>`）
+    pub token: String,
+    /// 租户自己的 DashScope API Key，未设置时使用共享的 api.key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// 每个 UTC 自然日最多提交的任务数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs_per_day: Option<usize>,
+    /// 每个任务最多的分镜数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_scenes: Option<usize>,
+    /// 输入文本的最大字符数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_text_length: Option<usize>,
+    /// 同时运行的任务上限，超出的任务在队列中等待
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+    /// 每个分镜最多生成的候选图片数（`image.n`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_candidates: Option<u32>,
+    /// 是否允许图生视频（按视频时长计费），默认不允许
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_animation: bool,
+}
+
+impl Settings {
+    pub fn qwen_options(&self) -> QwenOptions {
+        QwenOptions {
+            text_model: self.api.text_model.clone(),
+            image: ImageParams {
+                model: self.api.image_model.clone(),
+                style: self.image.style.clone(),
+                size: self.image.size.clone(),
+                n: self.image.n,
+                negative_prompt: self.image.negative_prompt.clone(),
+                seed: self.image.seed,
+                ref_img: self.image.ref_img.clone(),
+                ref_strength: self.image.ref_strength,
+                ref_mode: self.image.ref_mode.clone(),
+                prompt_extend: self.image.prompt_extend,
+            },
+            animation: AnimationOptions {
+                model: self.animation.model.clone(),
+                resolution: self.animation.resolution.clone(),
+                duration: self.animation.duration,
+                prompt_extend: self.animation.prompt_extend,
+            },
+            poll: PollOptions {
+                interval: Duration::from_secs_f64(self.api.poll_interval.max(0.0)),
+                max_interval: Duration::from_secs_f64(self.api.poll_max_interval.max(0.0)),
+                backoff: self.api.poll_backoff,
+                timeout: Duration::from_secs_f64(self.api.poll_timeout.max(0.0)),
+            },
+        }
+    }
+
+    /// 成片画面尺寸，与生成图片的尺寸一致
+    pub fn canvas(&self) -> (u32, u32) {
+        self.image
+            .size
+            .split_once(['*', 'x'])
+            .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+            .unwrap_or((1280, 720))
+    }
+
+    pub fn pacing(&self) -> Pacing {
+        Pacing {
+            trim_threshold: self
+                .speech
+                .trim_silence
+                .then_some(self.speech.silence_threshold),
+            sentence_pause: self.speech.sentence_pause,
+            scene_pause: self.speech.scene_pause,
+            lead_in: self.speech.lead_in,
+            tail: self.speech.tail,
+        }
+    }
+
+    pub fn subtitle_options(&self) -> SubtitleOptions {
+        SubtitleOptions {
+            style: self.subtitle.style,
+            highlight_color: self.subtitle.highlight_color.clone(),
+            font_file: self.subtitle.font_file.clone(),
+            font_name: self.subtitle.font_name.clone(),
+            font_size: self.subtitle.font_size,
+        }
+    }
+
+    pub fn music_options(&self) -> Option<MusicOptions> {
+        self.audio.music.clone().map(|path| MusicOptions {
+            path,
+            volume_db: self.audio.music_volume,
+            duck_db: self.audio.duck_db,
+            fade: self.audio.music_fade,
+        })
+    }
+
+    pub fn loudness_target(&self) -> Option<LoudnessTarget> {
+        self.audio.loudness.target().map(|mut target| {
+            target.integrated = self.audio.target_lufs.unwrap_or(target.integrated);
+            target.true_peak = self.audio.true_peak.unwrap_or(target.true_peak);
+            target
+        })
+    }
+}
@@ -0,0 +1,91 @@
+/// 内置风格预设
+#[derive(Debug)]
+pub struct BuiltinPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// 与 auto-video.toml 相同结构的 TOML 片段
+    pub settings: &'static str,
+}
+
+/// 内置预设只设置风格相关的项；背景音乐依赖本地文件，需要在用户预设中指定
+pub const BUILTIN_PRESETS: &[BuiltinPreset] = &[
+    BuiltinPreset {
+        name: "documentary",
+        description: "纪实摄影画面，沉稳男声讲解，白色整句字幕，淡入淡出转场",
+        settings: r##"
+[image]
+style = "<photography>"
+negative_prompt = "cartoon, anime, illustration, text, watermark, lowres"
+
+[voice]
+voice = "Elias"
+rate = 0.95
+
+[subtitle]
+style = "plain"
+
+[video]
+transition = "fade"
+transition_duration = 0.5
+
+[audio]
+music_volume = -22.0
+duck_db = -14.0
+"##,
+    },
+    BuiltinPreset {
+        name: "anime",
+        description: "日系动漫画面，活泼女声，粉色卡拉 OK 字幕，闪白转场",
+        settings: r##"
+[image]
+style = "<anime>"
+negative_prompt = "photo, realistic, text, watermark, lowres"
+
+[voice]
+voice = "Cherry"
+rate = 1.05
+
+[subtitle]
+style = "karaoke"
+highlight_color = "#FF6EC7"
+
+[video]
+transition = "flash"
+transition_duration = 0.3
+
+[audio]
+music_volume = -16.0
+duck_db = -10.0
+"##,
+    },
+    BuiltinPreset {
+        name: "storybook",
+        description: "水彩绘本画面，温和慢速旁白，暖黄卡拉 OK 字幕，淡入淡出转场",
+        settings: r##"
+[image]
+style = "<watercolor>"
+negative_prompt = "photo, realistic, dark, text, watermark"
+
+[voice]
+voice = "Li"
+rate = 0.9
+
+[subtitle]
+style = "karaoke"
+highlight_color = "#FFC857"
+
+[video]
+transition = "fade"
+transition_duration = 0.8
+
+[audio]
+music_volume = -20.0
+"##,
+    },
+];
+
+pub fn builtin(name: &str) -> Option<&'static BuiltinPreset> {
+    BUILTIN_PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+}
@@ -3,28 +3,31 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum VideoError {
     #[error("API error: {0}")]
-    ApiError(String),
+    Api(String),
 
     #[error("Scene processing error: {0}")]
-    SceneError(String),
+    Scene(String),
 
     #[error("Video generation error: {0}")]
-    VideoGenerationError(String),
+    VideoGeneration(String),
 
     #[error("IO error: {0}")]
-    IoError(#[from] std::io::Error),
+    Io(#[from] std::io::Error),
 
     #[error("HTTP request error: {0}")]
-    HttpError(#[from] reqwest::Error),
+    Http(#[from] reqwest::Error),
 
     #[error("JSON parsing error: {0}")]
-    JsonError(#[from] serde_json::Error),
-
-    #[error("Environment variable error: {0}")]
-    EnvError(String),
+    Json(#[from] serde_json::Error),
 
     #[error("FFmpeg error: {0}")]
-    FfmpegError(String),
+    Ffmpeg(String),
+
+    #[error("Media validation error: {0}")]
+    Media(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
 }
 
 pub type Result<T> = std::result::Result<T, VideoError>;
@@ -1,15 +1,28 @@
 mod api;
+mod config;
 mod error;
+mod manifest;
+mod media;
+mod progress;
 mod scene;
+mod server;
+mod speech;
 mod video;
 
 use anyhow::Context;
-use api::QwenClient;
-use clap::Parser;
+use api::{ImageAnimator, QwenClient, QwenOptions};
+use clap::{Parser, Subcommand};
+use config::{CliOverrides, LoadedConfig, Settings};
 use error::Result;
-use scene::Scene;
-use tracing::{error, info};
-use video::VideoGenerator;
+use manifest::{ImageRecord, Manifest};
+use media::{AudioFormat, ImageFit, LoudnessPreset, LoudnessTarget};
+use progress::{Event, EventBus, Stage};
+use scene::{candidate, CandidateScorer, CastMember, Scene, SceneMedia, Storyboard};
+use speech::{Lexicon, Pacing, Pronunciation, VoiceCatalogue, VoiceSettings};
+use tracing::{error, info, warn};
+use video::{
+    footage, MusicOptions, RenderSummary, SubtitleOptions, SubtitleStyle, Transition, VideoGenerator,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "auto-video")]
@@ -24,12 +37,12 @@ struct Args {
     file: Option<String>,
 
     /// Output video file path
-    #[arg(short, long, default_value = "output.mp4")]
-    output: String,
+    #[arg(short, long)]
+    output: Option<String>,
 
     /// Working directory for temporary files
-    #[arg(short = 'w', long, default_value = "./output")]
-    work_dir: String,
+    #[arg(short = 'w', long)]
+    work_dir: Option<String>,
 
     /// Skip image generation (use existing images)
     #[arg(long)]
@@ -38,6 +51,322 @@ struct Args {
     /// DashScope API key
     #[arg(long)]
     api_key: Option<String>,
+
+    /// Wanx image style, e.g. <photography>, <anime>, <watercolor>
+    #[arg(long)]
+    image_style: Option<String>,
+
+    /// Number of image candidates to generate per scene
+    #[arg(long)]
+    candidates: Option<u32>,
+
+    /// Things the generated images should avoid
+    #[arg(long)]
+    negative_prompt: Option<String>,
+
+    /// How images that don't match the canvas are fitted: crop (smart crop), blur (blurred background) or pad
+    #[arg(long, value_enum)]
+    image_fit: Option<ImageFit>,
+
+    /// Image seed, for reproducible images
+    #[arg(long)]
+    seed: Option<u32>,
+
+    /// Animate scene images with an image-to-video model (Ken Burns stills on failure)
+    #[arg(long)]
+    animate: bool,
+
+    /// Narration audio format
+    #[arg(long, value_enum)]
+    narration_format: Option<AudioFormat>,
+
+    /// Narrator voice (see --list-voices)
+    #[arg(long)]
+    voice: Option<String>,
+
+    /// Narration language, e.g. Chinese, English, Auto
+    #[arg(long)]
+    language: Option<String>,
+
+    /// Speech rate multiplier (1.0 = normal)
+    #[arg(long)]
+    speech_rate: Option<f64>,
+
+    /// Pitch multiplier (1.0 = normal)
+    #[arg(long)]
+    pitch: Option<f64>,
+
+    /// Narration volume 0-100 (50 = unchanged)
+    #[arg(long)]
+    volume: Option<f64>,
+
+    /// Narration sample rate in Hz
+    #[arg(long)]
+    sample_rate: Option<u32>,
+
+    /// Pronunciation lexicon (TOML or JSON); defaults to lexicon.toml/lexicon.json in the work directory
+    #[arg(long)]
+    lexicon: Option<String>,
+
+    /// Read numbers, dates, percentages and currency exactly as written
+    #[arg(long)]
+    no_normalize: bool,
+
+    /// Pause between sentences in seconds
+    #[arg(long)]
+    sentence_pause: Option<f64>,
+
+    /// Pause between scenes in seconds
+    #[arg(long)]
+    scene_pause: Option<f64>,
+
+    /// Silence before the first scene in seconds
+    #[arg(long)]
+    lead_in: Option<f64>,
+
+    /// Silence after the last scene in seconds
+    #[arg(long)]
+    tail: Option<f64>,
+
+    /// Level in dB below which leading/trailing speech audio is trimmed
+    #[arg(long, allow_hyphen_values = true)]
+    silence_threshold: Option<f64>,
+
+    /// Keep the leading/trailing silence returned by TTS
+    #[arg(long)]
+    no_trim_silence: bool,
+
+    /// Assign a voice to a dialogue speaker, e.g. --cast 小明=Ethan (repeatable)
+    #[arg(long, value_parser = parse_cast)]
+    cast: Vec<(String, String)>,
+
+    /// Subtitle style: whole lines, or karaoke-style word highlighting
+    #[arg(long, value_enum)]
+    subtitle_style: Option<SubtitleStyle>,
+
+    /// Highlight colour for karaoke subtitles
+    #[arg(long)]
+    highlight_color: Option<String>,
+
+    /// Transition between scenes
+    #[arg(long, value_enum)]
+    transition: Option<Transition>,
+
+    /// Transition duration in seconds
+    #[arg(long)]
+    transition_duration: Option<f64>,
+
+    /// Background music file, or a directory to pick a track from
+    #[arg(long)]
+    music: Option<String>,
+
+    /// Background music volume in dB
+    #[arg(long, allow_hyphen_values = true)]
+    music_volume: Option<f64>,
+
+    /// Extra music attenuation in dB while narration is playing
+    #[arg(long, allow_hyphen_values = true)]
+    duck_db: Option<f64>,
+
+    /// Background music fade-in/out duration in seconds
+    #[arg(long)]
+    music_fade: Option<f64>,
+
+    /// Sound effect library; scene sound cues refer to its files by tag
+    #[arg(long)]
+    sfx_dir: Option<String>,
+
+    /// Folder of local images and video clips; scene media refers to its files by tag
+    #[arg(long)]
+    media_dir: Option<String>,
+
+    /// Loudness normalisation target for the final mix
+    #[arg(long, value_enum)]
+    loudness: Option<LoudnessPreset>,
+
+    /// Override the integrated loudness target in LUFS
+    #[arg(long, allow_hyphen_values = true)]
+    target_lufs: Option<f64>,
+
+    /// Override the true-peak ceiling in dBTP
+    #[arg(long, allow_hyphen_values = true)]
+    true_peak: Option<f64>,
+
+    /// List available voices and exit
+    #[arg(long)]
+    list_voices: bool,
+
+    /// Config file, merged over ./auto-video.toml and the user config
+    #[arg(long)]
+    config: Option<String>,
+
+    /// Style preset bundling image style, voice, subtitles, transition and music (see `config presets`)
+    #[arg(long)]
+    preset: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspect the layered configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Choose which image candidate a scene uses; the next run re-renders with it
+    Pick {
+        /// Scene index
+        #[arg(long)]
+        scene: usize,
+        /// Candidate number, starting at 1
+        #[arg(long)]
+        candidate: usize,
+    },
+    /// Run the HTTP service: submit jobs with POST /jobs and poll GET /jobs/{id}
+    Serve {
+        /// Address to listen on [default: 127.0.0.1:8080]
+        #[arg(long)]
+        bind: Option<String>,
+        /// Number of jobs that run at the same time [default: 2]
+        #[arg(long)]
+        workers: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the effective configuration and where each value came from
+    Show,
+    /// List built-in and user-defined style presets
+    Presets,
+}
+
+impl Args {
+    /// 命令行显式给出的配置项，覆盖配置文件与环境变量
+    fn overrides(&self) -> CliOverrides {
+        let mut cli = CliOverrides::default();
+        cli.set("preset", "--preset", self.preset.as_ref());
+        cli.set("project.output", "--output", self.output.as_ref());
+        cli.set("project.work_dir", "--work-dir", self.work_dir.as_ref());
+        cli.set("project.narration_format", "--narration-format", self.narration_format);
+        cli.set("api.key", "--api-key", self.api_key.as_ref());
+        cli.set("image.style", "--image-style", self.image_style.as_ref());
+        cli.set("image.fit", "--image-fit", self.image_fit);
+        cli.set("image.negative_prompt", "--negative-prompt", self.negative_prompt.as_ref());
+        cli.set("image.seed", "--seed", self.seed);
+        cli.set("image.n", "--candidates", self.candidates);
+        cli.set("animation.enabled", "--animate", self.animate.then_some(true));
+        cli.set("voice.voice", "--voice", self.voice.as_ref());
+        cli.set("voice.language", "--language", self.language.as_ref());
+        cli.set("voice.rate", "--speech-rate", self.speech_rate);
+        cli.set("voice.pitch", "--pitch", self.pitch);
+        cli.set("voice.volume", "--volume", self.volume);
+        cli.set("voice.sample_rate", "--sample-rate", self.sample_rate);
+        cli.set("speech.lexicon", "--lexicon", self.lexicon.as_ref());
+        cli.set("speech.normalize", "--no-normalize", self.no_normalize.then_some(false));
+        cli.set("speech.sentence_pause", "--sentence-pause", self.sentence_pause);
+        cli.set("speech.scene_pause", "--scene-pause", self.scene_pause);
+        cli.set("speech.lead_in", "--lead-in", self.lead_in);
+        cli.set("speech.tail", "--tail", self.tail);
+        cli.set("speech.silence_threshold", "--silence-threshold", self.silence_threshold);
+        cli.set("speech.trim_silence", "--no-trim-silence", self.no_trim_silence.then_some(false));
+        for (speaker, voice) in &self.cast {
+            cli.set(format!("cast.{}", speaker), "--cast", Some(voice));
+        }
+        cli.set("subtitle.style", "--subtitle-style", self.subtitle_style);
+        cli.set("subtitle.highlight_color", "--highlight-color", self.highlight_color.as_ref());
+        cli.set("video.transition", "--transition", self.transition);
+        cli.set("video.transition_duration", "--transition-duration", self.transition_duration);
+        cli.set("audio.music", "--music", self.music.as_ref());
+        cli.set("audio.music_volume", "--music-volume", self.music_volume);
+        cli.set("audio.duck_db", "--duck-db", self.duck_db);
+        cli.set("audio.music_fade", "--music-fade", self.music_fade);
+        cli.set("audio.sfx_dir", "--sfx-dir", self.sfx_dir.as_ref());
+        cli.set("video.media_dir", "--media-dir", self.media_dir.as_ref());
+        cli.set("audio.loudness", "--loudness", self.loudness);
+        cli.set("audio.target_lufs", "--target-lufs", self.target_lufs);
+        cli.set("audio.true_peak", "--true-peak", self.true_peak);
+        if let Some(Command::Serve { bind, workers }) = &self.command {
+            cli.set("server.bind", "--bind", bind.as_ref());
+            cli.set("server.workers", "--workers", *workers);
+        }
+        cli
+    }
+}
+
+/// 视频生成流程的运行参数
+struct GenerationOptions {
+    work_dir: String,
+    output_path: String,
+    skip_images: bool,
+    narration_format: AudioFormat,
+    qwen: QwenOptions,
+    score_command: Option<String>,
+    animate: bool,
+    voice: VoiceSettings,
+    pacing: Pacing,
+    lexicon: Option<String>,
+    normalize: bool,
+    cast: Vec<(String, String)>,
+    subtitles: SubtitleOptions,
+    fps: u32,
+    canvas: (u32, u32),
+    image_fit: ImageFit,
+    transition: Transition,
+    transition_duration: f64,
+    media_dir: Option<String>,
+    music: Option<MusicOptions>,
+    sfx_dir: Option<String>,
+    loudness: Option<LoudnessTarget>,
+    /// 分镜数上限（服务模式的租户配额），超出时在生成图片与语音前失败
+    max_scenes: Option<usize>,
+    events: EventBus,
+}
+
+impl GenerationOptions {
+    fn new(settings: &Settings, skip_images: bool) -> Self {
+        Self {
+            work_dir: settings.project.work_dir.clone(),
+            output_path: settings.project.output.clone(),
+            skip_images,
+            narration_format: settings.project.narration_format,
+            qwen: settings.qwen_options(),
+            score_command: settings.image.score_command.clone(),
+            animate: settings.animation.enabled,
+            voice: settings.voice.clone(),
+            pacing: settings.pacing(),
+            lexicon: settings.speech.lexicon.clone(),
+            normalize: settings.speech.normalize,
+            cast: settings
+                .cast
+                .iter()
+                .map(|(speaker, voice)| (speaker.clone(), voice.clone()))
+                .collect(),
+            subtitles: settings.subtitle_options(),
+            fps: settings.video.fps,
+            canvas: settings.canvas(),
+            image_fit: settings.image.fit,
+            transition: settings.video.transition,
+            transition_duration: settings.video.transition_duration,
+            media_dir: settings.video.media_dir.clone(),
+            music: settings.music_options(),
+            sfx_dir: settings.audio.sfx_dir.clone(),
+            loudness: settings.loudness_target(),
+            max_scenes: None,
+            events: EventBus::default(),
+        }
+    }
+}
+
+fn parse_cast(value: &str) -> std::result::Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((speaker, voice)) if !speaker.trim().is_empty() && !voice.trim().is_empty() => {
+            Ok((speaker.trim().to_string(), voice.trim().to_string()))
+        }
+        _ => Err(format!("expected SPEAKER=VOICE, got '{}'", value)),
+    }
 }
 
 #[tokio::main]
@@ -55,13 +384,46 @@ async fn main() -> anyhow::Result<()> {
     // 解析命令行参数
     let args = Args::parse();
 
-    // 获取 API key
-    let api_key = if let Some(key) = args.api_key {
-        key
-    } else if let Ok(key) = std::env::var("DASHSCOPE_API_KEY") {
+    // 合并配置文件、环境变量与命令行参数
+    let loaded = match LoadedConfig::load(args.config.as_deref(), args.overrides()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match &args.command {
+        Some(Command::Config { action }) => {
+            match action {
+                ConfigAction::Show => loaded.print(),
+                ConfigAction::Presets => loaded.print_presets(),
+            }
+            return Ok(());
+        }
+        Some(Command::Pick { scene, candidate }) => {
+            pick_candidate(&loaded.settings.project.work_dir, *scene, *candidate).await?;
+            return Ok(());
+        }
+        Some(Command::Serve { .. }) => {
+            server::serve(loaded).await?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    if args.list_voices {
+        VoiceCatalogue::QWEN_TTS_FLASH.print();
+        return Ok(());
+    }
+
+    let settings = loaded.settings;
+
+    // 获取 API key（命令行、DASHSCOPE_API_KEY 或配置文件）
+    let api_key = if let Some(key) = settings.api.key.clone() {
         key
     } else {
-        eprintln!("Error: DASHSCOPE_API_KEY not found. Please set it via --api-key or DASHSCOPE_API_KEY environment variable");
+        eprintln!("Error: DASHSCOPE_API_KEY not found. Please set it via --api-key, DASHSCOPE_API_KEY environment variable or api.key in auto-video.toml");
         std::process::exit(1);
     };
 
@@ -81,15 +443,44 @@ async fn main() -> anyhow::Result<()> {
     info!("Input text length: {} characters", input_text.len());
 
     // 创建工作目录
-    tokio::fs::create_dir_all(&args.work_dir)
+    tokio::fs::create_dir_all(&settings.project.work_dir)
         .await
         .context("Failed to create work directory")?;
 
-    // 运行视频生成流程
-    if let Err(e) = run_generation(input_text, api_key, args.work_dir, args.output, args.skip_images).await {
-        error!("Video generation failed: {}", e);
-        std::process::exit(1);
+    let options = GenerationOptions::new(&settings, args.skip_images);
+
+    // 进度显示订阅流程事件，收到结束事件后退出
+    let events = options.events.clone();
+    let display = tokio::spawn(progress::display(events.subscribe()));
+
+    // 创建千问客户端
+    let client = QwenClient::new(api_key)
+        .with_options(options.qwen.clone())
+        .with_events(events.clone());
+
+    // 运行视频生成流程；Ctrl-C 中断时取消尚未结束的远端任务
+    tokio::select! {
+        result = run_generation(input_text, &client, options) => match result {
+            Ok(summary) => events.emit(Event::Finished {
+                output: settings.project.output.clone(),
+                duration: summary.duration,
+            }),
+            Err(e) => {
+                events.emit(Event::Failed { error: e.to_string() });
+                display.await.ok();
+                error!("Video generation failed: {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ = tokio::signal::ctrl_c() => {
+            warn!("Interrupted, cancelling pending tasks...");
+            client.tasks().cancel_pending().await;
+            events.emit(Event::Cancelled);
+            display.await.ok();
+            std::process::exit(130);
+        }
     }
+    display.await.ok();
 
     info!("Video generation completed successfully!");
     Ok(())
@@ -97,81 +488,509 @@ async fn main() -> anyhow::Result<()> {
 
 async fn run_generation(
     input_text: String,
-    api_key: String,
-    work_dir: String,
-    output_path: String,
-    skip_images: bool,
-) -> Result<()> {
-    // 1. 创建千问客户端
-    let client = QwenClient::new(api_key);
-
-    // 2. 生成分镜或使用现有图片
-    let mut scenes = if skip_images {
-        info!("Skipping scene generation, using existing images...");
-        // 先生成分镜以获取字幕文本
-        info!("Generating scenes for subtitles...");
-        let mut scenes = client.generate_scenes(&input_text).await?;
-        info!("Generated {} scenes", scenes.len());
-        
-        // 使用现有图片
-        for scene in scenes.iter_mut() {
-            let image_path = format!("{}/scene_{}.png", work_dir, scene.index);
-            if tokio::fs::metadata(&image_path).await.is_ok() {
-                scene.image_path = Some(image_path);
+    client: &QwenClient,
+    options: GenerationOptions,
+) -> Result<RenderSummary> {
+    let GenerationOptions {
+        work_dir,
+        output_path,
+        skip_images,
+        narration_format,
+        qwen,
+        score_command,
+        animate,
+        voice,
+        pacing,
+        lexicon,
+        normalize,
+        cast,
+        subtitles,
+        fps,
+        canvas,
+        image_fit,
+        transition,
+        transition_duration,
+        media_dir,
+        music,
+        sfx_dir,
+        loudness,
+        max_scenes,
+        events,
+    } = options;
+
+    let image_params = qwen.image;
+
+    // 2. 生成分镜（工作目录中已有对应原文的分镜脚本时直接复用）
+    info!("Step 1/4: Generating scenes...");
+    events.stage(Stage::Storyboard);
+    let storyboard_path = format!("{}/storyboard.json", work_dir);
+    let mut storyboard = match Storyboard::load(&storyboard_path).await? {
+        Some(storyboard) if storyboard.source_text == input_text => {
+            info!("Using existing storyboard: {}", storyboard_path);
+            storyboard
+        }
+        existing => {
+            if existing.is_some() {
+                events.warn("Input text changed, regenerating storyboard...");
             }
+            let sfx_tags = match &sfx_dir {
+                Some(dir) => video::mix::sfx_tags(dir).await.map_err(|e| {
+                    error::VideoError::Media(format!(
+                        "Failed to read sound effect library {}: {}",
+                        dir, e
+                    ))
+                })?,
+                None => Vec::new(),
+            };
+            let (bible, scenes) = client
+                .generate_scenes(&input_text, &sfx_tags, max_scenes)
+                .await?;
+            let storyboard = Storyboard::new(input_text.clone(), bible, scenes);
+            storyboard.save(&storyboard_path).await?;
+            storyboard
         }
-        
-        scenes
-    } else {
-        info!("Step 1/4: Generating scenes...");
-        let scenes = client.generate_scenes(&input_text).await?;
-        info!("Generated {} scenes", scenes.len());
-        scenes
     };
+    info!("Generated {} scenes", storyboard.scenes.len());
+    // 上限已写入提示词，模型仍超出时只保留前面的分镜，在生成图片与语音之前截断
+    if let Some(max) = max_scenes {
+        if storyboard.scenes.len() > max {
+            events.warn(format!(
+                "Storyboard has {} scenes, keeping the first {} allowed per job",
+                storyboard.scenes.len(),
+                max
+            ));
+            storyboard.scenes.truncate(max);
+            storyboard.save(&storyboard_path).await?;
+        }
+    }
+    events.stage_finished(Stage::Storyboard);
+
+    for scene in storyboard.scenes.iter_mut() {
+        scene.ensure_lines();
+    }
+    video::mix::resolve_cues(&mut storyboard.scenes, sfx_dir.as_deref()).await;
+    footage::resolve_media(&mut storyboard.scenes, media_dir.as_deref()).await;
+
+    // 命令行指定的角色音色覆盖分镜脚本中的 cast
+    for (speaker, voice_name) in cast {
+        let member = storyboard.cast.entry(speaker).or_insert_with(CastMember::default);
+        member.voice.voice = Some(voice_name);
+    }
+    for speaker in storyboard.speakers() {
+        if storyboard.cast.get(&speaker).and_then(|m| m.voice.voice.as_ref()).is_none() {
+            events.warn(format!("Speaker '{}' has no voice in cast, using the narrator voice", speaker));
+        }
+    }
+    storyboard.apply_cast_styles();
+
+    // 在调用任何付费接口前先校验音色参数：默认 → 项目 → 命令行 → 分镜 → 角色
+    let catalogue = client.voice_catalogue();
+    let project_voice = storyboard.voice.overlay(&voice);
+    let narrator = catalogue.resolve(&project_voice)?;
+    let mut voices = Vec::new();
+    for scene in &storyboard.scenes {
+        let scene_voice = match &scene.voice {
+            Some(scene_voice) => project_voice.overlay(scene_voice),
+            None => project_voice.clone(),
+        };
+        let mut line_voices = Vec::new();
+        for line in &scene.lines {
+            let mut settings = match line.speaker.as_ref().and_then(|s| storyboard.cast.get(s)) {
+                Some(member) => scene_voice.overlay(&member.voice),
+                None => scene_voice.clone(),
+            };
+            // 采样率是整条旁白的属性，不允许按分镜或角色覆盖
+            settings.sample_rate = project_voice.sample_rate;
+            line_voices.push(catalogue.resolve(&settings)?);
+        }
+        voices.push(line_voices);
+    }
+    let pronunciation = Pronunciation {
+        lexicon: Lexicon::discover(lexicon.as_deref(), &work_dir).await?,
+        normalize,
+    };
+    let bible = &storyboard.bible;
+    let scenes = &mut storyboard.scenes;
 
     // 3. 为每个分镜生成图片（支持断点续传）
+    let manifest_path = format!("{}/manifest.json", work_dir);
+    events.stage(Stage::Images);
     if !skip_images {
         info!("Step 2/4: Generating images for each scene...");
         let scene_count = scenes.len();
+        let mut image_records = Vec::new();
+        let scorer = CandidateScorer::new(score_command.as_deref());
+        let previous = Manifest::load(&manifest_path).await?;
+        let image_done = |scene: usize, idx: usize| {
+            events.emit(Event::ImageDone {
+                scene,
+                done: idx + 1,
+                total: scene_count,
+            })
+        };
         for (idx, scene) in scenes.iter_mut().enumerate() {
-            let image_path = format!("{}/scene_{}.png", work_dir, scene.index);
-            
-            // 检查图片是否已存在，跳过已生成的
-            if tokio::fs::metadata(&image_path).await.is_ok() {
+            // 使用本地素材的分镜不生成图片
+            if footage::has_local_media(scene) {
+                scene.image_path = footage::local_image(scene);
+                image_done(scene.index, idx);
+                continue;
+            }
+
+            // 检查图片是否已存在且可解码，跳过已生成的
+            if let Some(image_path) = existing_image(&work_dir, scene.index).await {
                 info!("Scene {} image already exists, skipping...", scene.index);
                 scene.image_path = Some(image_path);
+                image_done(scene.index, idx);
                 continue;
             }
-            
-            client.generate_image(&scene.description, &image_path).await?;
-            scene.image_path = Some(image_path.clone());
+
+            // 生效顺序：全局配置 → 角色/场景设定 → 分镜覆盖
+            let mut params = image_params.overlay(&bible.image_overrides(scene));
+            if let Some(overrides) = &scene.image {
+                params = params.overlay(overrides);
+            }
+            if params.ref_img.is_some() && !params.supports_ref_img() {
+                events.warn(format!(
+                    "Image model {} does not accept reference images, ignoring ref_img",
+                    params.model
+                ));
+                params.ref_img = None;
+            }
+            let prompt = bible.image_prompt(scene);
+
+            // 上次已生成候选但未完成选择时直接复用；种子沿用上次的记录，
+            // 没有记录时不能确定生成这些候选的种子，不再自行选取
+            let mut candidates = candidate::find_candidates(&work_dir, scene.index).await;
+            if candidates.is_empty() {
+                params.ensure_seed();
+                let stem = format!("{}/scene_{}", work_dir, scene.index);
+                candidates = client
+                    .generate_images(&prompt, &params, &stem)
+                    .await?;
+            } else {
+                info!("Scene {} has {} candidates, selecting...", scene.index, candidates.len());
+                if let Some(record) = previous.images.iter().find(|r| r.scene == scene.index) {
+                    params.seed = record.params.seed;
+                }
+            }
+            let (best, scores) = if candidates.len() > 1 {
+                scorer.select(&candidates).await
+            } else {
+                (0, Vec::new())
+            };
+            let image_path = candidate::choose(&work_dir, scene.index, &candidates[best]).await?;
+            image_records.push(ImageRecord {
+                scene: scene.index,
+                path: image_path.clone(),
+                prompt,
+                params,
+                candidates,
+                scores,
+                chosen: best + 1,
+            });
+            scene.image_path = Some(image_path);
             info!("Generated image for scene {} ({}/{})", scene.index, idx + 1, scene_count);
+            image_done(scene.index, idx);
+        }
+
+        // 立即记录生成参数，后续步骤失败时重跑会复用这些图片
+        if !image_records.is_empty() {
+            let mut manifest = Manifest::load(&manifest_path).await?;
+            manifest.record_images(image_records);
+            manifest.save(&manifest_path).await?;
         }
     } else {
-        info!("Step 2/4: Skipped image generation");
+        info!("Step 2/4: Skipped image generation, using existing images...");
+        for scene in scenes.iter_mut() {
+            scene.image_path = match footage::local_image(scene) {
+                Some(path) => Some(path),
+                None => provided_image(&work_dir, scene.index).await?,
+            };
+        }
+    }
+
+    // 图片统一规范化为画面尺寸，原图保持不变
+    normalize_images(scenes, &work_dir, canvas, image_fit, &events).await;
+    events.stage_finished(Stage::Images);
+
+    if animate {
+        info!("Animating scene images...");
+        events.stage(Stage::Animation);
+        animate_scenes(client, scenes, &work_dir, &events).await;
+        events.stage_finished(Stage::Animation);
     }
 
-    // 4. 生成语音（支持断点续传）
+    // 4. 按分镜生成语音（支持断点续传），场景时长取旁白实际时长加停顿
     info!("Step 3/4: Generating speech...");
-    let audio_path = format!("{}/audio.mp3", work_dir);
-    
-    if tokio::fs::metadata(&audio_path).await.is_ok() {
-        info!("Audio file already exists, skipping speech generation...");
-    } else {
-        let full_text: String = scenes
-            .iter()
-            .map(|s| s.subtitle.clone())
-            .collect::<Vec<_>>()
-            .join("。");
-        client.generate_speech(&full_text, &audio_path).await?;
+    events.stage(Stage::Speech);
+    let mut clips = Vec::new();
+    let scene_count = scenes.len();
+    for (position, (scene, line_voices)) in scenes.iter_mut().zip(&voices).enumerate() {
+        let mut t = pacing.before_scene(position);
+        if let Some(pause) = pause_clip(&work_dir, t, narrator.sample_rate).await? {
+            clips.push(pause);
+        }
+
+        if scene.lines.is_empty() {
+            // 无台词的分镜以静音占位，保持音画对齐
+            let clip_path = format!("{}/narration_{}.wav", work_dir, scene.index);
+            media::generate_silence(scene.duration, narrator.sample_rate, &clip_path).await?;
+            clips.push(clip_path);
+            t += scene.duration;
+        }
+
+        // 逐句合成，不同说话人使用各自的音色
+        let line_count = scene.lines.len();
+        for (k, (line, voice)) in scene.lines.iter_mut().zip(line_voices).enumerate() {
+            let stem = format!("narration_{}_{}", scene.index, k);
+            let clip_path = format!("{}/{}.wav", work_dir, stem);
+
+            let existing = existing_audio(&work_dir, &stem).await;
+            let (clip_path, duration) = if let Some(existing) = existing {
+                info!("Scene {} line {} narration already exists, skipping...", scene.index, k);
+                existing
+            } else {
+                info!(
+                    "Scene {} line {} voice: {} ({})",
+                    scene.index,
+                    k,
+                    voice.voice,
+                    line.speaker.as_deref().unwrap_or("narrator")
+                );
+                // 朗读文本经词典与规范化改写，字幕保留原文
+                let spoken = pronunciation.spoken(&line.text, &voice.language);
+                if spoken != line.text {
+                    info!("Scene {} line {} spoken as: {}", scene.index, k, spoken);
+                }
+                let duration = client.generate_speech(&spoken, &clip_path, voice, &pacing).await?;
+                (clip_path, duration)
+            };
+            line.duration = duration;
+            line.start = t;
+            let expand = pronunciation.word_expander(&line.text, &voice.language);
+            line.words = speech::estimate_word_timings(&line.text, line.duration, expand);
+            t += line.duration;
+            clips.push(clip_path);
+
+            if k + 1 < line_count {
+                if let Some(pause) = pause_clip(&work_dir, pacing.sentence_pause, narrator.sample_rate).await? {
+                    clips.push(pause);
+                    t += pacing.sentence_pause;
+                }
+            }
+        }
+
+        let after = pacing.after_scene(position, scene_count);
+        if let Some(pause) = pause_clip(&work_dir, after, narrator.sample_rate).await? {
+            clips.push(pause);
+            t += after;
+        }
+        scene.duration = t;
+        events.emit(Event::SpeechDone {
+            scene: scene.index,
+            done: position + 1,
+            total: scene_count,
+        });
     }
+    events.stage_finished(Stage::Speech);
 
-    // 5. 合成视频
+    // 各分镜旁白无损合并为母带，再按配置格式编码一次
+    let master_path = format!("{}/narration.wav", work_dir);
+    let audio_path = format!("{}/narration.{}", work_dir, narration_format.extension());
+    media::concat_audio(&clips, &master_path).await?;
+    if audio_path != master_path {
+        media::encode_audio(&master_path, narration_format, &audio_path).await?;
+    }
+    info!(
+        "Narration duration: {:.2}s",
+        scenes.iter().map(|s| s.duration).sum::<f64>()
+    );
+
+    // 5. 合成视频（需要重新混音时从无损母带出发，避免二次有损压缩）
     info!("Step 4/4: Generating final video...");
-    let video_gen = VideoGenerator::new(work_dir.clone());
-    video_gen
-        .generate_video(&scenes, &audio_path, &output_path)
+    events.stage(Stage::Render);
+    let mux_audio = if music.is_some() || loudness.is_some() || video::has_sound_cues(scenes) {
+        &master_path
+    } else {
+        &audio_path
+    };
+    let video_gen = VideoGenerator::new(work_dir.clone())
+        .with_subtitles(subtitles)
+        .with_fps(fps)
+        .with_canvas(canvas.0, canvas.1)
+        .with_transition(transition, transition_duration)
+        .with_music(music)
+        .with_loudness(loudness)
+        .with_events(events.clone());
+    let summary = video_gen
+        .generate_video(scenes, mux_audio, &output_path)
         .await?;
 
+    // 6. 记录本次合成结果
+    let mut manifest = Manifest::load(&manifest_path).await?;
+    manifest.output = output_path.clone();
+    manifest.duration = summary.duration;
+    manifest.loudness = summary.loudness.clone();
+    manifest.save(&manifest_path).await?;
+
+    events.stage_finished(Stage::Render);
+    Ok(summary)
+}
+
+/// 生成（或复用）指定时长的停顿静音，时长不为正时返回 None
+async fn pause_clip(work_dir: &str, duration: f64, sample_rate: u32) -> Result<Option<String>> {
+    let millis = (duration * 1000.0).round() as i64;
+    if millis <= 0 {
+        return Ok(None);
+    }
+    let stem = format!("pause_{}ms_{}", millis, sample_rate);
+    if let Some((path, _)) = existing_audio(work_dir, &stem).await {
+        return Ok(Some(path));
+    }
+    let path = format!("{}/{}.wav", work_dir, stem);
+    media::generate_silence(millis as f64 / 1000.0, sample_rate, &path).await?;
+    Ok(Some(path))
+}
+
+/// 查找用户提供的分镜图片（`--skip-images`），只读校验，无法解码时报错而不删除
+async fn provided_image(work_dir: &str, index: usize) -> Result<Option<String>> {
+    let stem = format!("scene_{}", index);
+    let Some(path) = media::find_existing(work_dir, &stem, media::IMAGE_EXTENSIONS).await else {
+        return Ok(None);
+    };
+    media::probe(&path).await?.require_image(&path)?;
+    Ok(Some(path))
+}
+
+/// 查找流程自己生成的分镜图片，无法解码的会被删除以便重新生成
+async fn existing_image(work_dir: &str, index: usize) -> Option<String> {
+    let stem = format!("scene_{}", index);
+    let path = media::find_existing(work_dir, &stem, media::IMAGE_EXTENSIONS).await?;
+    match media::validate_image(&path).await {
+        Ok(path) => Some(path),
+        Err(e) => {
+            warn!("Existing image {} is unusable, discarding: {}", path, e);
+            tokio::fs::remove_file(&path).await.ok();
+            None
+        }
+    }
+}
+
+/// 把分镜图片规范化为画面尺寸，副本写入 `normalized/` 目录；失败时沿用原图，由合成阶段补边
+async fn normalize_images(
+    scenes: &mut [Scene],
+    work_dir: &str,
+    canvas: (u32, u32),
+    fit: ImageFit,
+    events: &EventBus,
+) {
+    let dir = format!("{}/normalized", work_dir);
+    for scene in scenes.iter_mut() {
+        let Some(source) = scene.image_path.clone() else {
+            continue;
+        };
+        let stem = format!("scene_{}", scene.index);
+        match media::normalized_copy(&source, &dir, &stem, canvas, fit).await {
+            Ok(path) => scene.image_path = Some(path),
+            Err(e) => events.warn(format!(
+                "Scene {}: failed to normalize {}, using it as is: {}",
+                scene.index, source, e
+            )),
+        }
+    }
+}
+
+/// 用图生视频模型为每个分镜生成视频（支持断点续传），失败的分镜改用 Ken Burns 效果
+async fn animate_scenes<A: ImageAnimator>(
+    animator: &A,
+    scenes: &mut [Scene],
+    work_dir: &str,
+    events: &EventBus,
+) {
+    for scene in scenes.iter_mut() {
+        if footage::has_local_media(scene) {
+            continue;
+        }
+        let Some(image_path) = scene.image_path.clone() else {
+            continue;
+        };
+
+        let clip_path = format!("{}/clip_{}.mp4", work_dir, scene.index);
+        let clip = match existing_clip(&clip_path).await {
+            Some(path) => {
+                info!("Scene {} clip already exists, skipping...", scene.index);
+                Ok(path)
+            }
+            None => {
+                let prompt = scene.motion.as_deref().unwrap_or(&scene.description);
+                animator.animate(&image_path, prompt, &clip_path).await
+            }
+        };
+        match clip {
+            Ok(path) => scene.media = Some(SceneMedia::generated_clip(path)),
+            Err(e) => {
+                events.warn(format!(
+                    "Scene {}: animation failed, using a Ken Burns still: {}",
+                    scene.index, e
+                ));
+                scene.ken_burns = true;
+            }
+        }
+    }
+}
+
+/// 检查已生成的视频片段，无法播放的会被删除以便重新生成
+async fn existing_clip(path: &str) -> Option<String> {
+    tokio::fs::metadata(path).await.ok()?;
+    match media::probe(path).await {
+        Ok(info) if info.video.is_some() && info.duration.is_some() => Some(path.to_string()),
+        _ => {
+            warn!("Existing clip {} is unusable, regenerating", path);
+            tokio::fs::remove_file(path).await.ok();
+            None
+        }
+    }
+}
+
+/// 手动指定分镜使用的候选图片，并更新 manifest.json
+async fn pick_candidate(work_dir: &str, scene: usize, k: usize) -> Result<()> {
+    let candidates = candidate::find_candidates(work_dir, scene).await;
+    if k == 0 || k > candidates.len() {
+        return Err(error::VideoError::Scene(format!(
+            "Scene {} has {} image candidates in {}, cannot pick candidate {}",
+            scene,
+            candidates.len(),
+            work_dir,
+            k
+        )));
+    }
+
+    let path = candidate::choose(work_dir, scene, &candidates[k - 1]).await?;
+    // 由旧图片生成的视频片段随之作废
+    tokio::fs::remove_file(format!("{}/clip_{}.mp4", work_dir, scene))
+        .await
+        .ok();
+    let manifest_path = format!("{}/manifest.json", work_dir);
+    let mut manifest = Manifest::load(&manifest_path).await?;
+    manifest.choose_image(scene, k, path);
+    manifest.save(&manifest_path).await?;
+
+    println!(
+        "Scene {} now uses candidate {}; run auto-video again to re-render",
+        scene, k
+    );
     Ok(())
 }
+
+/// 检查已生成的音频，返回校验（可能修正扩展名）后的路径与时长；无法解码的会被删除以便重新生成
+async fn existing_audio(work_dir: &str, stem: &str) -> Option<(String, f64)> {
+    let path = media::find_existing(work_dir, stem, media::AUDIO_EXTENSIONS).await?;
+    match media::validate_audio(&path).await {
+        Ok(existing) => Some(existing),
+        Err(e) => {
+            warn!("Existing audio {} is unusable, regenerating: {}", path, e);
+            tokio::fs::remove_file(&path).await.ok();
+            None
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::api::ImageParams;
+use crate::error::Result;
+use crate::media::LoudnessReport;
+use serde::{Deserialize, Serialize};
+
+/// 项目清单，保存在工作目录的 manifest.json 中，记录每次合成的实际参数与测量结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// 最近一次输出的视频路径
+    #[serde(default)]
+    pub output: String,
+    /// 成片时长（秒）
+    #[serde(default)]
+    pub duration: f64,
+    /// 最终混音的响度测量结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loudness: Option<LoudnessReport>,
+    /// 每个分镜图片的生成参数，按分镜序号排列
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageRecord>,
+}
+
+/// 一张分镜图片的生成记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageRecord {
+    pub scene: usize,
+    /// 选中的图片（复制为 scene_{i}.*）
+    pub path: String,
+    pub prompt: String,
+    pub params: ImageParams,
+    /// 全部候选图片 scene_{i}_{k}.*
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    /// 自动选择时各候选的得分
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scores: Vec<f64>,
+    /// 选中的候选编号，从 1 开始
+    #[serde(default = "ImageRecord::first_candidate")]
+    pub chosen: usize,
+}
+
+impl ImageRecord {
+    fn first_candidate() -> usize {
+        1
+    }
+}
+
+impl Manifest {
+    /// 读取清单，文件不存在时返回空清单
+    pub async fn load(path: &str) -> Result<Self> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 记录新生成的图片，替换同一分镜的旧记录；复用的图片保留原有记录
+    pub fn record_images(&mut self, records: Vec<ImageRecord>) {
+        for record in records {
+            self.images.retain(|existing| existing.scene != record.scene);
+            self.images.push(record);
+        }
+        self.images.sort_by_key(|record| record.scene);
+    }
+
+    /// 记录手动选择的候选图片
+    pub fn choose_image(&mut self, scene: usize, candidate: usize, path: String) {
+        if let Some(record) = self.images.iter_mut().find(|record| record.scene == scene) {
+            record.chosen = candidate;
+            record.path = path;
+        }
+    }
+
+    pub async fn save(&self, path: &str) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, content).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use crate::error::{Result, VideoError};
+use crate::media::MediaInfo;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::process::Command;
+use tracing::info;
+
+/// 旁白音频的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+    Mp3,
+    Aac,
+    Opus,
+}
+
+impl AudioFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Aac => "m4a",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            AudioFormat::Wav => &["-c:a", "pcm_s16le"],
+            AudioFormat::Flac => &["-c:a", "flac"],
+            AudioFormat::Mp3 => &["-c:a", "libmp3lame", "-b:a", "192k"],
+            AudioFormat::Aac => &["-c:a", "aac", "-b:a", "192k"],
+            AudioFormat::Opus => &["-c:a", "libopus", "-b:a", "128k"],
+        }
+    }
+}
+
+/// 根据文件头识别音频格式，返回对应扩展名
+pub fn sniff_audio_extension(data: &[u8]) -> Option<&'static str> {
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE" {
+        Some("wav")
+    } else if data.starts_with(b"fLaC") {
+        Some("flac")
+    } else if data.starts_with(b"OggS") {
+        Some("ogg")
+    } else if data.starts_with(b"ID3")
+        || (data.len() >= 2 && data[0] == 0xFF && data[1] & 0xE0 == 0xE0)
+    {
+        Some("mp3")
+    } else if data.len() >= 8 && &data[4..8] == b"ftyp" {
+        Some("m4a")
+    } else {
+        None
+    }
+}
+
+/// 音频编码能否直接封装进 MP4 而无需重新编码
+pub fn mp4_compatible(info: &MediaInfo) -> bool {
+    info.audio
+        .as_ref()
+        .is_some_and(|audio| matches!(audio.codec.as_str(), "aac" | "mp3" | "opus"))
+}
+
+/// 将音频编码为指定格式
+pub async fn encode_audio(input: &str, format: AudioFormat, output: &str) -> Result<()> {
+    info!("Encoding {} to {:?}: {}", input, format, output);
+
+    let mut args = vec!["-y", "-i", input, "-vn"];
+    args.extend_from_slice(format.codec_args());
+    args.push(output);
+
+    let output_cmd = Command::new("ffmpeg")
+        .args(&args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run FFmpeg: {}", e)))?;
+
+    if !output_cmd.status.success() {
+        let error = String::from_utf8_lossy(&output_cmd.stderr);
+        return Err(VideoError::Ffmpeg(format!(
+            "FFmpeg audio encoding failed: {}",
+            error
+        )));
+    }
+
+    Ok(())
+}
+
+/// 无损合并多个音频文件为 PCM WAV
+pub async fn concat_audio(files: &[String], output: &str) -> Result<()> {
+    // 创建 FFmpeg concat 列表文件
+    let concat_list = format!("{}.concat.txt", output);
+    let mut content = String::new();
+    for file in files {
+        // 转换为绝对路径
+        let abs_path = PathBuf::from(file).canonicalize().map_err(|e| {
+            VideoError::Ffmpeg(format!("Failed to get absolute path for {}: {}", file, e))
+        })?;
+        content.push_str(&format!("file '{}'\n", abs_path.display()));
+    }
+    tokio::fs::write(&concat_list, content).await?;
+
+    // 输出无损 PCM，避免多次有损编码
+    let output_cmd = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-f",
+            "concat",
+            "-safe",
+            "0",
+            "-i",
+            &concat_list,
+            "-c:a",
+            "pcm_s16le",
+            output,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to merge audio: {}", e)))?;
+
+    // 删除临时列表文件
+    tokio::fs::remove_file(&concat_list).await.ok();
+
+    if !output_cmd.status.success() {
+        let error = String::from_utf8_lossy(&output_cmd.stderr);
+        return Err(VideoError::Ffmpeg(format!(
+            "FFmpeg merge failed: {}",
+            error
+        )));
+    }
+
+    Ok(())
+}
+
+/// 对音频应用 FFmpeg 滤镜，输出 PCM WAV
+pub async fn filter_audio(input: &str, filter: &str, output: &str) -> Result<()> {
+    let output_cmd = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-i",
+            input,
+            "-af",
+            filter,
+            "-c:a",
+            "pcm_s16le",
+            output,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run FFmpeg: {}", e)))?;
+
+    if !output_cmd.status.success() {
+        let error = String::from_utf8_lossy(&output_cmd.stderr);
+        return Err(VideoError::Ffmpeg(format!(
+            "FFmpeg audio filter failed: {}",
+            error
+        )));
+    }
+
+    Ok(())
+}
+
+/// 生成指定时长的静音 PCM WAV
+pub async fn generate_silence(duration: f64, sample_rate: u32, output: &str) -> Result<()> {
+    let source = format!("anullsrc=r={}:cl=mono", sample_rate);
+    let output_cmd = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-f",
+            "lavfi",
+            "-i",
+            &source,
+            "-t",
+            &format!("{:.3}", duration.max(0.0)),
+            "-c:a",
+            "pcm_s16le",
+            output,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run FFmpeg: {}", e)))?;
+
+    if !output_cmd.status.success() {
+        let error = String::from_utf8_lossy(&output_cmd.stderr);
+        return Err(VideoError::Ffmpeg(format!(
+            "FFmpeg silence generation failed: {}",
+            error
+        )));
+    }
+
+    Ok(())
+}
+
+/// 裁剪音频首尾低于阈值的静音，各保留 50ms 避免切掉辅音，输出 PCM WAV
+pub async fn trim_silence(input: &str, threshold_db: f64, output: &str) -> Result<()> {
+    let trim = format!(
+        "silenceremove=start_periods=1:start_threshold={:.1}dB:start_silence=0.05",
+        threshold_db
+    );
+    // silenceremove 只处理开头，反转两次以裁剪结尾
+    let filter = format!("{},areverse,{},areverse", trim, trim);
+    filter_audio(input, &filter, output).await
+}
@@ -0,0 +1,186 @@
+use crate::error::{Result, VideoError};
+use crate::media::probe::{average_luma, probe};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use tokio::process::Command;
+use tracing::info;
+
+/// 智能裁剪时沿溢出方向尝试的裁剪位置数量
+const CROP_POSITIONS: u32 = 5;
+
+/// 图片比例与画面不一致时的适配方式，分辨率不足时都会先放大
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFit {
+    /// 放大铺满画面，裁掉溢出部分，保留细节最多的区域
+    Crop,
+    /// 完整保留图片，空白处填充同一图片的模糊放大版本
+    Blur,
+    /// 完整保留图片，空白处填充黑边
+    Pad,
+}
+
+impl ImageFit {
+    pub fn name(self) -> &'static str {
+        match self {
+            ImageFit::Crop => "crop",
+            ImageFit::Blur => "blur",
+            ImageFit::Pad => "pad",
+        }
+    }
+}
+
+/// 返回 `input` 规范化到画面尺寸后的副本 `{dir}/{stem}_{宽}x{高}_{方式}.png`，原图保持不变
+///
+/// 尺寸已与画面一致的图片直接返回原路径；副本比原图新时直接复用。
+pub async fn normalized_copy(
+    input: &str,
+    dir: &str,
+    stem: &str,
+    canvas: (u32, u32),
+    fit: ImageFit,
+) -> Result<String> {
+    let info = probe(input).await?;
+    let video = info.require_image(input)?;
+    if (video.width, video.height) == canvas {
+        return Ok(input.to_string());
+    }
+
+    let output = format!(
+        "{}/{}_{}x{}_{}.png",
+        dir,
+        stem,
+        canvas.0,
+        canvas.1,
+        fit.name()
+    );
+    let normalized = modified(&output).await;
+    if normalized.is_some() && normalized >= modified(input).await {
+        info!("Using normalized image {}", output);
+        return Ok(output);
+    }
+
+    tokio::fs::create_dir_all(dir).await?;
+    normalize_image(input, canvas, fit, &output).await?;
+    Ok(output)
+}
+
+async fn modified(path: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// 把图片规范化为 `canvas` 尺寸写入 `output`，不修改原图
+pub async fn normalize_image(
+    input: &str,
+    canvas: (u32, u32),
+    fit: ImageFit,
+    output: &str,
+) -> Result<()> {
+    let info = probe(input).await?;
+    let video = info.require_image(input)?;
+    let (width, height) = canvas;
+    let source = (video.width, video.height);
+
+    let filter = match fit {
+        ImageFit::Crop => {
+            let (scaled_width, scaled_height) = cover_size(source, canvas);
+            let (x, y) = smart_crop(input, (scaled_width, scaled_height), canvas).await?;
+            format!(
+                "scale={}:{}:flags=lanczos,crop={}:{}:{}:{},setsar=1",
+                scaled_width, scaled_height, width, height, x, y
+            )
+        }
+        ImageFit::Blur => format!(
+            "split[bg][fg];\
+             [bg]scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h},boxblur=20:2[bg];\
+             [fg]scale={w}:{h}:force_original_aspect_ratio=decrease:flags=lanczos[fg];\
+             [bg][fg]overlay=(W-w)/2:(H-h)/2,setsar=1",
+            w = width,
+            h = height
+        ),
+        ImageFit::Pad => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease:flags=lanczos,\
+             pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1",
+            w = width,
+            h = height
+        ),
+    };
+
+    let result = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-v",
+            "error",
+            "-i",
+            input,
+            "-filter_complex",
+            &filter,
+            "-frames:v",
+            "1",
+            output,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run ffmpeg: {}", e)))?;
+
+    if !result.status.success() {
+        let error = String::from_utf8_lossy(&result.stderr);
+        return Err(VideoError::Media(format!(
+            "Failed to normalize {}: {}",
+            input,
+            error.trim()
+        )));
+    }
+
+    info!(
+        "Normalized {} ({}x{}) to {}x{} by {}: {}",
+        input,
+        source.0,
+        source.1,
+        width,
+        height,
+        fit.name(),
+        output
+    );
+    Ok(())
+}
+
+/// 按比例放大到恰好铺满画面的尺寸
+fn cover_size(source: (u32, u32), canvas: (u32, u32)) -> (u32, u32) {
+    let scale =
+        (canvas.0 as f64 / source.0.max(1) as f64).max(canvas.1 as f64 / source.1.max(1) as f64);
+    (
+        ((source.0 as f64 * scale).round() as u32).max(canvas.0),
+        ((source.1 as f64 * scale).round() as u32).max(canvas.1),
+    )
+}
+
+/// 在溢出方向上均匀取几个裁剪位置，选边缘密度最高（细节最多）的一个；同分时优先居中
+async fn smart_crop(input: &str, scaled: (u32, u32), canvas: (u32, u32)) -> Result<(u32, u32)> {
+    let overflow = (scaled.0 - canvas.0, scaled.1 - canvas.1);
+    let center = (overflow.0 / 2, overflow.1 / 2);
+    if overflow.0 + overflow.1 < 2 {
+        return Ok(center);
+    }
+
+    let mut best = (center, f64::NEG_INFINITY);
+    let mut positions: Vec<u32> = (0..CROP_POSITIONS).collect();
+    positions.sort_by_key(|k| (2 * k).abs_diff(CROP_POSITIONS - 1));
+    for k in positions {
+        let offset = (
+            overflow.0 * k / (CROP_POSITIONS - 1),
+            overflow.1 * k / (CROP_POSITIONS - 1),
+        );
+        let filter = format!(
+            "scale={}:{},crop={}:{}:{}:{},edgedetect",
+            scaled.0, scaled.1, canvas.0, canvas.1, offset.0, offset.1
+        );
+        let density = average_luma(input, Some(&filter)).await?;
+        if density > best.1 {
+            best = (offset, density);
+        }
+    }
+    Ok(best.0)
+}
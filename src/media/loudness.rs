@@ -0,0 +1,172 @@
+use crate::error::{Result, VideoError};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tracing::info;
+
+/// 响度标准预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoudnessPreset {
+    /// 网络平台：-14 LUFS，真峰值 -1 dBTP
+    Web,
+    /// 播客：-16 LUFS，真峰值 -1.5 dBTP
+    Podcast,
+    /// 广电 EBU R128：-23 LUFS，真峰值 -1 dBTP
+    Broadcast,
+    /// 不做响度标准化
+    Off,
+}
+
+/// 响度标准化目标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessTarget {
+    /// 综合响度（LUFS）
+    pub integrated: f64,
+    /// 真峰值上限（dBTP）
+    pub true_peak: f64,
+    /// 响度范围（LU）
+    pub lra: f64,
+}
+
+impl LoudnessPreset {
+    pub fn target(self) -> Option<LoudnessTarget> {
+        let (integrated, true_peak, lra) = match self {
+            LoudnessPreset::Web => (-14.0, -1.0, 11.0),
+            LoudnessPreset::Podcast => (-16.0, -1.5, 11.0),
+            LoudnessPreset::Broadcast => (-23.0, -1.0, 7.0),
+            LoudnessPreset::Off => return None,
+        };
+        Some(LoudnessTarget {
+            integrated,
+            true_peak,
+            lra,
+        })
+    }
+}
+
+/// 两遍 loudnorm 的测量结果，写入项目 manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoudnessReport {
+    pub target: LoudnessTarget,
+    /// 第一遍测量到的原始响度
+    pub input_i: f64,
+    pub input_tp: f64,
+    pub input_lra: f64,
+    pub input_thresh: f64,
+    /// 第二遍处理后的响度
+    pub output_i: f64,
+    pub output_tp: f64,
+    pub output_lra: f64,
+    pub target_offset: f64,
+}
+
+/// loudnorm 以字符串形式输出 JSON 中的数值
+#[derive(Debug, Deserialize)]
+struct LoudnormStats {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    output_i: String,
+    output_tp: String,
+    output_lra: String,
+    target_offset: String,
+}
+
+impl LoudnormStats {
+    fn value(field: &str, value: &str) -> Result<f64> {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| VideoError::Ffmpeg(format!("Invalid loudnorm {}: {}", field, value)))
+    }
+}
+
+/// 两遍 EBU R128 响度标准化，输出 PCM WAV
+pub async fn normalize_loudness(
+    input: &str,
+    target: LoudnessTarget,
+    output: &str,
+) -> Result<LoudnessReport> {
+    let base = format!(
+        "loudnorm=I={}:TP={}:LRA={}",
+        target.integrated, target.true_peak, target.lra
+    );
+
+    // 第一遍：只测量
+    info!("Measuring loudness of {}...", input);
+    let measured = run_loudnorm(
+        &["-i", input],
+        &format!("{}:print_format=json", base),
+        &["-f", "null", "-"],
+    )
+    .await?;
+    let input_i = LoudnormStats::value("input_i", &measured.input_i)?;
+    let input_tp = LoudnormStats::value("input_tp", &measured.input_tp)?;
+    let input_lra = LoudnormStats::value("input_lra", &measured.input_lra)?;
+    let input_thresh = LoudnormStats::value("input_thresh", &measured.input_thresh)?;
+    let offset = LoudnormStats::value("target_offset", &measured.target_offset)?;
+
+    // 第二遍：代入测量值做线性增益
+    let filter = format!(
+        "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true:print_format=json",
+        base, input_i, input_tp, input_lra, input_thresh, offset
+    );
+    let applied = run_loudnorm(
+        &["-i", input],
+        &filter,
+        &["-c:a", "pcm_s16le", "-ar", "48000", output],
+    )
+    .await?;
+
+    let report = LoudnessReport {
+        target,
+        input_i,
+        input_tp,
+        input_lra,
+        input_thresh,
+        output_i: LoudnormStats::value("output_i", &applied.output_i)?,
+        output_tp: LoudnormStats::value("output_tp", &applied.output_tp)?,
+        output_lra: LoudnormStats::value("output_lra", &applied.output_lra)?,
+        target_offset: LoudnormStats::value("target_offset", &applied.target_offset)?,
+    };
+    info!(
+        "Loudness {:.1} LUFS / {:.1} dBTP -> {:.1} LUFS / {:.1} dBTP",
+        report.input_i, report.input_tp, report.output_i, report.output_tp
+    );
+    Ok(report)
+}
+
+async fn run_loudnorm(inputs: &[&str], filter: &str, outputs: &[&str]) -> Result<LoudnormStats> {
+    let output = Command::new("ffmpeg")
+        .arg("-y")
+        .args(["-hide_banner", "-nostats"])
+        .args(inputs)
+        .args(["-af", filter])
+        .args(outputs)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run FFmpeg: {}", e)))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(VideoError::Ffmpeg(format!(
+            "FFmpeg loudnorm failed: {}",
+            stderr
+        )));
+    }
+
+    // loudnorm 把统计结果作为 stderr 末尾的 JSON 对象输出
+    let json = stderr
+        .rfind('{')
+        .and_then(|start| {
+            stderr[start..]
+                .rfind('}')
+                .map(|end| &stderr[start..=start + end])
+        })
+        .ok_or_else(|| VideoError::Ffmpeg("No loudnorm statistics in FFmpeg output".to_string()))?;
+
+    Ok(serde_json::from_str(json)?)
+}
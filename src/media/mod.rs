@@ -0,0 +1,15 @@
+pub mod audio;
+pub mod image;
+pub mod loudness;
+pub mod probe;
+
+pub use audio::{
+    concat_audio, encode_audio, filter_audio, generate_silence, mp4_compatible,
+    sniff_audio_extension, trim_silence, AudioFormat,
+};
+pub use image::{normalized_copy, ImageFit};
+pub use loudness::{normalize_loudness, LoudnessPreset, LoudnessReport, LoudnessTarget};
+pub use probe::{
+    find_existing, image_brightness, probe, validate_audio, validate_image, MediaInfo,
+    AUDIO_EXTENSIONS, IMAGE_EXTENSIONS,
+};
@@ -0,0 +1,313 @@
+use crate::error::{Result, VideoError};
+use serde::Deserialize;
+use std::path::Path;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// 图片素材允许的扩展名
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "webp", "bmp"];
+
+/// `fix_extension` 可能给音频使用的扩展名，查找已生成的音频时依次尝试
+pub const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "opus", "ogg", "m4a"];
+
+/// ffprobe 解析出的媒体信息
+#[derive(Debug, Clone)]
+pub struct MediaInfo {
+    /// 容器格式名（ffprobe 的 format_name）
+    pub format_name: String,
+    /// 时长（秒），静态图片为 None
+    pub duration: Option<f64>,
+    /// 第一条视频流（图片也算视频流）
+    pub video: Option<VideoStreamInfo>,
+    /// 第一条音频流
+    pub audio: Option<AudioStreamInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VideoStreamInfo {
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioStreamInfo {
+    pub codec: String,
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    sample_rate: Option<String>,
+    channels: Option<u32>,
+    duration: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeFormat {
+    format_name: Option<String>,
+    duration: Option<String>,
+}
+
+impl MediaInfo {
+    /// 根据实际编码推断应使用的文件扩展名
+    pub fn preferred_extension(&self) -> Option<&'static str> {
+        if self.audio.is_none() {
+            if let Some(video) = &self.video {
+                return match video.codec.as_str() {
+                    "png" => Some("png"),
+                    "mjpeg" | "jpeg" => Some("jpg"),
+                    "webp" => Some("webp"),
+                    "bmp" => Some("bmp"),
+                    _ => None,
+                };
+            }
+        }
+
+        if self.video.is_none() {
+            if let Some(audio) = &self.audio {
+                let format = self.format_name.split(',').next().unwrap_or_default();
+                return match (format, audio.codec.as_str()) {
+                    ("wav", _) => Some("wav"),
+                    ("mp3", _) | (_, "mp3") => Some("mp3"),
+                    ("flac", _) => Some("flac"),
+                    ("ogg", "opus") => Some("opus"),
+                    ("ogg", _) => Some("ogg"),
+                    ("mov", _) | (_, "aac") => Some("m4a"),
+                    _ => None,
+                };
+            }
+        }
+
+        None
+    }
+
+    /// 确认为可解码的图片，返回宽高
+    pub fn require_image(&self, path: &str) -> Result<&VideoStreamInfo> {
+        match &self.video {
+            Some(video) if video.width > 0 && video.height > 0 => Ok(video),
+            _ => Err(VideoError::Media(format!(
+                "{} is not a decodable image (format: {})",
+                path, self.format_name
+            ))),
+        }
+    }
+
+    /// 确认为可解码的音频，返回其时长
+    pub fn require_audio(&self, path: &str) -> Result<f64> {
+        if self.audio.is_none() {
+            return Err(VideoError::Media(format!(
+                "{} has no audio stream (format: {})",
+                path, self.format_name
+            )));
+        }
+
+        match self.duration {
+            Some(duration) if duration > 0.0 => Ok(duration),
+            _ => Err(VideoError::Media(format!(
+                "{} has no usable duration",
+                path
+            ))),
+        }
+    }
+}
+
+/// 调用 ffprobe 读取媒体信息
+pub async fn probe(path: &str) -> Result<MediaInfo> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+            path,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run ffprobe: {}", e)))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(VideoError::Media(format!(
+            "ffprobe could not read {}: {}",
+            path,
+            error.trim()
+        )));
+    }
+
+    let parsed: ProbeOutput = serde_json::from_slice(&output.stdout)?;
+    Ok(parse_probe_output(parsed))
+}
+
+fn parse_probe_output(parsed: ProbeOutput) -> MediaInfo {
+    let parse_f64 = |value: &Option<String>| value.as_deref().and_then(|v| v.parse::<f64>().ok());
+
+    let mut video = None;
+    let mut audio = None;
+    let mut stream_duration = None;
+
+    for stream in &parsed.streams {
+        let codec = stream.codec_name.clone().unwrap_or_default();
+        match stream.codec_type.as_deref() {
+            Some("video") if video.is_none() => {
+                video = Some(VideoStreamInfo {
+                    codec,
+                    width: stream.width.unwrap_or(0),
+                    height: stream.height.unwrap_or(0),
+                });
+            }
+            Some("audio") if audio.is_none() => {
+                audio = Some(AudioStreamInfo {
+                    codec,
+                    sample_rate: stream
+                        .sample_rate
+                        .as_deref()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0),
+                    channels: stream.channels.unwrap_or(0),
+                });
+                stream_duration = parse_f64(&stream.duration);
+            }
+            _ => {}
+        }
+    }
+
+    let format = parsed.format;
+    let format_name = format
+        .as_ref()
+        .and_then(|f| f.format_name.clone())
+        .unwrap_or_default();
+
+    // 静态图片的 duration 没有意义，只对音视频保留
+    let is_still = audio.is_none()
+        && video.as_ref().is_some_and(|v: &VideoStreamInfo| {
+            matches!(v.codec.as_str(), "png" | "mjpeg" | "jpeg" | "webp" | "bmp")
+        });
+    let duration = if is_still {
+        None
+    } else {
+        format
+            .as_ref()
+            .and_then(|f| parse_f64(&f.duration))
+            .or(stream_duration)
+    };
+
+    MediaInfo {
+        format_name,
+        duration,
+        video,
+        audio,
+    }
+}
+
+/// 检查图片素材，必要时修正扩展名，返回最终路径
+pub async fn validate_image(path: &str) -> Result<String> {
+    let info = probe(path).await?;
+    let video = info.require_image(path)?;
+    info!(
+        "Image {} ok: {} {}x{}",
+        path, video.codec, video.width, video.height
+    );
+    fix_extension(path, &info).await
+}
+
+/// 检查音频素材，必要时修正扩展名，返回最终路径和时长
+pub async fn validate_audio(path: &str) -> Result<(String, f64)> {
+    let info = probe(path).await?;
+    let duration = info.require_audio(path)?;
+    if let Some(audio) = &info.audio {
+        info!(
+            "Audio {} ok: {} {}Hz {}ch {:.2}s",
+            path, audio.codec, audio.sample_rate, audio.channels, duration
+        );
+    }
+    let path = fix_extension(path, &info).await?;
+    Ok((path, duration))
+}
+
+/// 若扩展名与实际格式不符则重命名文件
+pub async fn fix_extension(path: &str, info: &MediaInfo) -> Result<String> {
+    let Some(extension) = info.preferred_extension() else {
+        return Ok(path.to_string());
+    };
+
+    let current = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    if current.as_deref() == Some(extension) {
+        return Ok(path.to_string());
+    }
+
+    let fixed = Path::new(path)
+        .with_extension(extension)
+        .to_string_lossy()
+        .into_owned();
+    warn!(
+        "{} is actually {}, renaming to {}",
+        path, info.format_name, fixed
+    );
+    tokio::fs::rename(path, &fixed).await?;
+    Ok(fixed)
+}
+
+/// 在目录中查找已存在的素材（任意允许的扩展名）
+pub async fn find_existing(dir: &str, stem: &str, extensions: &[&str]) -> Option<String> {
+    for extension in extensions {
+        let candidate = format!("{}/{}.{}", dir, stem, extension);
+        if tokio::fs::metadata(&candidate).await.is_ok() {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// 图片平均亮度（0-255，signalstats 的 YAVG）
+pub async fn image_brightness(path: &str) -> Result<f64> {
+    average_luma(path, None).await
+}
+
+/// 经 `filter` 处理后画面的平均亮度（0-255），用于亮度、边缘密度等简单度量
+pub async fn average_luma(path: &str, filter: Option<&str>) -> Result<f64> {
+    let stats = "signalstats,metadata=print:key=lavfi.signalstats.YAVG:file=-";
+    let filter = match filter {
+        Some(filter) => format!("{},{}", filter, stats),
+        None => stats.to_string(),
+    };
+    let output = Command::new("ffmpeg")
+        .args(["-v", "error", "-i", path, "-vf", &filter, "-f", "null", "-"])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Ffmpeg(format!("Failed to run ffmpeg: {}", e)))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(VideoError::Media(format!(
+            "Failed to measure luma of {}: {}",
+            path,
+            error.trim()
+        )));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("lavfi.signalstats.YAVG="))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| VideoError::Media(format!("No luma reported for {}", path)))
+}
@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// 事件总线的缓冲容量，订阅者落后超过该数量时会丢失最早的事件
+const EVENT_CAPACITY: usize = 256;
+
+/// 生成流程的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Queued,
+    Storyboard,
+    Images,
+    Animation,
+    Speech,
+    Render,
+    Finished,
+}
+
+/// 生成流程中的事件，命令行进度显示与服务模式的任务状态、SSE 推送都从同一事件总线订阅
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    StageStarted {
+        stage: Stage,
+    },
+    StageFinished {
+        stage: Stage,
+    },
+    /// 分镜图片已就绪（生成、复用或使用本地素材）
+    ImageDone {
+        scene: usize,
+        done: usize,
+        total: usize,
+    },
+    /// 一个 TTS 分段合成完成
+    SpeechChunkDone {
+        file: String,
+        chunk: usize,
+        chunks: usize,
+    },
+    /// 分镜旁白已就绪
+    SpeechDone {
+        scene: usize,
+        done: usize,
+        total: usize,
+    },
+    /// 合成进度（0-100）
+    RenderProgress {
+        percent: f64,
+    },
+    Warning {
+        message: String,
+    },
+    /// 最终结果
+    Finished {
+        output: String,
+        duration: f64,
+    },
+    Failed {
+        error: String,
+    },
+    Cancelled,
+}
+
+impl Event {
+    /// 事件类型名，与序列化后的 `type` 字段一致
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::StageStarted { .. } => "stage_started",
+            Event::StageFinished { .. } => "stage_finished",
+            Event::ImageDone { .. } => "image_done",
+            Event::SpeechChunkDone { .. } => "speech_chunk_done",
+            Event::SpeechDone { .. } => "speech_done",
+            Event::RenderProgress { .. } => "render_progress",
+            Event::Warning { .. } => "warning",
+            Event::Finished { .. } => "finished",
+            Event::Failed { .. } => "failed",
+            Event::Cancelled => "cancelled",
+        }
+    }
+
+    /// 流程结束的事件，之后不会再有事件
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Event::Finished { .. } | Event::Failed { .. } | Event::Cancelled
+        )
+    }
+}
+
+/// 当前阶段及阶段内已完成的分镜数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageProgress {
+    pub stage: Stage,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl StageProgress {
+    pub fn new(stage: Stage) -> Self {
+        Self {
+            stage,
+            done: 0,
+            total: 0,
+        }
+    }
+
+    /// 根据事件更新进度
+    pub fn apply(&mut self, event: &Event) {
+        match event {
+            Event::StageStarted { stage } => *self = Self::new(*stage),
+            Event::ImageDone { done, total, .. } | Event::SpeechDone { done, total, .. } => {
+                self.done = *done;
+                self.total = *total;
+            }
+            Event::RenderProgress { percent } => {
+                self.done = percent.round() as usize;
+                self.total = 100;
+            }
+            Event::Finished { .. } => *self = Self::new(Stage::Finished),
+            _ => {}
+        }
+    }
+}
+
+/// 事件总线：没有订阅者时发送的事件直接丢弃
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+}
+
+impl EventBus {
+    pub fn emit(&self, event: Event) {
+        self.sender.send(event).ok();
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// 进入新阶段
+    pub fn stage(&self, stage: Stage) {
+        self.emit(Event::StageStarted { stage });
+    }
+
+    /// 阶段结束
+    pub fn stage_finished(&self, stage: Stage) {
+        self.emit(Event::StageFinished { stage });
+    }
+
+    /// 记录警告日志并发送警告事件
+    pub fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+        warn!("{}", message);
+        self.emit(Event::Warning { message });
+    }
+}
+
+/// 命令行进度显示：把阶段与分镜进度打印到标准错误，直到流程结束或总线关闭
+///
+/// 警告与失败原因已写入日志，这里不再重复。
+pub async fn display(mut receiver: broadcast::Receiver<Event>) {
+    loop {
+        let event = match receiver.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        match &event {
+            Event::StageStarted { stage } => eprintln!("==> {:?}", stage),
+            Event::ImageDone { done, total, .. } => eprintln!("    images {}/{}", done, total),
+            Event::SpeechDone { done, total, .. } => eprintln!("    speech {}/{}", done, total),
+            Event::RenderProgress { percent } => eprintln!("    render {:.0}%", percent),
+            Event::Finished { output, duration } => {
+                eprintln!("==> Done: {} ({:.1}s)", output, duration)
+            }
+            Event::Cancelled => eprintln!("==> Cancelled"),
+            Event::StageFinished { .. }
+            | Event::SpeechChunkDone { .. }
+            | Event::Warning { .. }
+            | Event::Failed { .. } => {}
+        }
+        if event.is_terminal() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_progress_follows_events() {
+        let mut progress = StageProgress::new(Stage::Queued);
+        progress.apply(&Event::StageStarted {
+            stage: Stage::Images,
+        });
+        assert_eq!(progress, StageProgress::new(Stage::Images));
+
+        progress.apply(&Event::ImageDone {
+            scene: 2,
+            done: 1,
+            total: 4,
+        });
+        assert_eq!((progress.done, progress.total), (1, 4));
+
+        // 警告与分段事件不改变进度
+        progress.apply(&Event::Warning {
+            message: "slow".to_string(),
+        });
+        progress.apply(&Event::SpeechChunkDone {
+            file: "scene_1.mp3".to_string(),
+            chunk: 1,
+            chunks: 3,
+        });
+        assert_eq!(
+            (progress.stage, progress.done, progress.total),
+            (Stage::Images, 1, 4)
+        );
+    }
+
+    #[test]
+    fn stage_progress_resets_on_each_stage() {
+        let mut progress = StageProgress::new(Stage::Speech);
+        progress.apply(&Event::SpeechDone {
+            scene: 3,
+            done: 3,
+            total: 3,
+        });
+        progress.apply(&Event::StageStarted {
+            stage: Stage::Render,
+        });
+        assert_eq!((progress.done, progress.total), (0, 0));
+
+        progress.apply(&Event::RenderProgress { percent: 42.6 });
+        assert_eq!(
+            (progress.stage, progress.done, progress.total),
+            (Stage::Render, 43, 100)
+        );
+
+        progress.apply(&Event::Finished {
+            output: "output.mp4".to_string(),
+            duration: 12.0,
+        });
+        assert_eq!(progress, StageProgress::new(Stage::Finished));
+    }
+
+    #[test]
+    fn event_names_match_serialized_type() {
+        let events = [
+            Event::StageStarted {
+                stage: Stage::Storyboard,
+            },
+            Event::RenderProgress { percent: 1.0 },
+            Event::Cancelled,
+        ];
+        for event in events {
+            let value = serde_json::to_value(&event).unwrap();
+            assert_eq!(value["type"], event.name());
+        }
+    }
+}
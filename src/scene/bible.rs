@@ -0,0 +1,127 @@
+use crate::api::image::MAX_SEED;
+use crate::api::ImageOverrides;
+use crate::scene::Scene;
+use serde::{Deserialize, Serialize};
+
+/// 万相文生图提示词的长度上限（字符）
+const MAX_PROMPT_CHARS: usize = 800;
+
+/// 一个角色或场景的固定设定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BibleEntry {
+    pub name: String,
+    /// 英文外貌/环境描述，注入到每个相关分镜的图片提示词中
+    pub description: String,
+    /// 参考图 URL，模型支持时随请求传入
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_img: Option<String>,
+    /// 固定种子，相关分镜都使用同一种子
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+}
+
+/// 角色与场景设定集，由分镜阶段提取，保存在 storyboard.json 中可手动修改
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bible {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub characters: Vec<BibleEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub settings: Vec<BibleEntry>,
+}
+
+impl Bible {
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty() && self.settings.is_empty()
+    }
+
+    /// 为没有种子的角色按名字生成固定种子，同一角色在各分镜中保持一致
+    pub fn assign_seeds(&mut self) {
+        for character in &mut self.characters {
+            if character.seed.is_none() {
+                // FNV-1a
+                let hash = character.name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+                    (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+                });
+                character.seed = Some(hash % MAX_SEED);
+            }
+        }
+    }
+
+    /// 分镜中出现的角色：优先使用分镜标注的名单，没有标注时按名字是否出现在描述或台词中判断
+    pub fn characters_in(&self, scene: &Scene) -> Vec<&BibleEntry> {
+        if !scene.characters.is_empty() {
+            return scene
+                .characters
+                .iter()
+                .filter_map(|name| find(&self.characters, name))
+                .collect();
+        }
+
+        self.characters
+            .iter()
+            .filter(|character| {
+                let name = character.name.to_lowercase();
+                scene.description.to_lowercase().contains(&name)
+                    || scene.subtitle.to_lowercase().contains(&name)
+                    || scene.lines.iter().any(|line| {
+                        line.speaker
+                            .as_deref()
+                            .is_some_and(|speaker| speaker.eq_ignore_ascii_case(&character.name))
+                            || line.text.to_lowercase().contains(&name)
+                    })
+            })
+            .collect()
+    }
+
+    /// 分镜所在的场景设定
+    pub fn setting_of(&self, scene: &Scene) -> Option<&BibleEntry> {
+        scene
+            .setting
+            .as_deref()
+            .and_then(|name| find(&self.settings, name))
+    }
+
+    /// 注入角色与场景设定后的图片提示词
+    pub fn image_prompt(&self, scene: &Scene) -> String {
+        let mut prompt = scene.description.trim().to_string();
+        for character in self.characters_in(scene) {
+            prompt.push_str(&format!(
+                " {}: {}.",
+                character.name,
+                character.description.trim().trim_end_matches('.')
+            ));
+        }
+        if let Some(setting) = self.setting_of(scene) {
+            prompt.push_str(&format!(
+                " Setting, {}: {}.",
+                setting.name,
+                setting.description.trim().trim_end_matches('.')
+            ));
+        }
+
+        if prompt.chars().count() > MAX_PROMPT_CHARS {
+            prompt = prompt.chars().take(MAX_PROMPT_CHARS).collect();
+        }
+        prompt
+    }
+
+    /// 主要角色（第一个出场的角色）的参考图与种子，其次使用场景设定的
+    pub fn image_overrides(&self, scene: &Scene) -> ImageOverrides {
+        let entries: Vec<&BibleEntry> = self
+            .characters_in(scene)
+            .into_iter()
+            .chain(self.setting_of(scene))
+            .collect();
+        ImageOverrides {
+            ref_img: entries.iter().find_map(|entry| entry.ref_img.clone()),
+            seed: entries.iter().find_map(|entry| entry.seed),
+            ..ImageOverrides::default()
+        }
+    }
+}
+
+fn find<'a>(entries: &'a [BibleEntry], name: &str) -> Option<&'a BibleEntry> {
+    entries
+        .iter()
+        .find(|entry| entry.name.trim().eq_ignore_ascii_case(name.trim()))
+}
@@ -0,0 +1,140 @@
+use crate::error::{Result, VideoError};
+use crate::media;
+use std::path::Path;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// 分镜 `index` 的第 `k` 张候选图片的文件名（不含扩展名），k 从 1 开始
+pub fn candidate_stem(index: usize, k: usize) -> String {
+    format!("scene_{}_{}", index, k)
+}
+
+/// 列出分镜已有的候选图片，按编号排列
+pub async fn find_candidates(work_dir: &str, index: usize) -> Vec<String> {
+    let mut candidates = Vec::new();
+    while let Some(path) = media::find_existing(
+        work_dir,
+        &candidate_stem(index, candidates.len() + 1),
+        media::IMAGE_EXTENSIONS,
+    )
+    .await
+    {
+        candidates.push(path);
+    }
+    candidates
+}
+
+/// 把选中的候选图片复制为分镜图片 `scene_{index}.*`，重新合成时直接使用
+pub async fn choose(work_dir: &str, index: usize, candidate: &str) -> Result<String> {
+    for extension in media::IMAGE_EXTENSIONS {
+        let stale = format!("{}/scene_{}.{}", work_dir, index, extension);
+        if tokio::fs::metadata(&stale).await.is_ok() {
+            tokio::fs::remove_file(&stale).await?;
+        }
+    }
+
+    let extension = Path::new(candidate)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("png");
+    let chosen = format!("{}/scene_{}.{}", work_dir, index, extension);
+    tokio::fs::copy(candidate, &chosen).await?;
+    info!("Scene {} now uses {}", index, candidate);
+    Ok(chosen)
+}
+
+/// 候选图片的评分方式，分数越高越好
+#[derive(Debug, Clone, Default)]
+pub enum CandidateScorer {
+    /// 按分辨率与曝光的简单启发式评分
+    #[default]
+    Heuristic,
+    /// 外部评分命令：图片路径作为最后一个参数，标准输出的第一个数字为分数
+    Command(String),
+}
+
+impl CandidateScorer {
+    pub fn new(command: Option<&str>) -> Self {
+        match command {
+            Some(command) if !command.trim().is_empty() => Self::Command(command.to_string()),
+            _ => Self::Heuristic,
+        }
+    }
+
+    pub async fn score(&self, path: &str) -> Result<f64> {
+        match self {
+            Self::Heuristic => heuristic_score(path).await,
+            Self::Command(command) => command_score(command, path).await,
+        }
+    }
+
+    /// 为所有候选评分，返回得分最高者的下标和全部分数；评分失败的候选记为负无穷
+    pub async fn select(&self, candidates: &[String]) -> (usize, Vec<f64>) {
+        let mut scores = Vec::new();
+        for candidate in candidates {
+            let score = match self.score(candidate).await {
+                Ok(score) => score,
+                Err(e) => {
+                    warn!("Failed to score {}: {}", candidate, e);
+                    f64::NEG_INFINITY
+                }
+            };
+            info!("Candidate {} scored {:.3}", candidate, score);
+            scores.push(score);
+        }
+
+        // 同分时取编号较小的候选
+        let best = scores.iter().enumerate().fold(
+            0,
+            |best, (i, score)| if *score > scores[best] { i } else { best },
+        );
+        (best, scores)
+    }
+}
+
+/// 分辨率（以 1280x720 为满分）与曝光（平均亮度越接近中灰越好）各占一半
+async fn heuristic_score(path: &str) -> Result<f64> {
+    let info = media::probe(path).await?;
+    let video = info.require_image(path)?;
+    let resolution = (video.width as f64 * video.height as f64 / (1280.0 * 720.0)).min(1.0);
+
+    let brightness = media::image_brightness(path).await?;
+    let exposure = 1.0 - ((brightness - 128.0).abs() / 128.0).min(1.0);
+
+    Ok(0.5 * resolution + 0.5 * exposure)
+}
+
+async fn command_score(command: &str, path: &str) -> Result<f64> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| VideoError::Config("Empty score command".to_string()))?;
+    let output = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| VideoError::Scene(format!("Failed to run {}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(VideoError::Scene(format!(
+            "Score command failed for {}: {}",
+            path,
+            error.trim()
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .split_whitespace()
+        .find_map(|token| token.parse::<f64>().ok())
+        .ok_or_else(|| {
+            VideoError::Scene(format!(
+                "Score command printed no number for {}: {}",
+                path,
+                stdout.trim()
+            ))
+        })
+}
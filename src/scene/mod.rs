@@ -1,4 +1,161 @@
+pub mod bible;
+pub mod candidate;
+
+pub use bible::{Bible, BibleEntry};
+pub use candidate::CandidateScorer;
+
+use crate::api::ImageOverrides;
+use crate::error::Result;
+use crate::speech::{VoiceSettings, WordTiming};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 未在 cast 中指定颜色的角色依次使用的字幕颜色
+const SPEAKER_COLORS: &[&str] = &["#FFD166", "#4CC9F0", "#06D6A0", "#F72585", "#B5E48C", "#FF9F1C"];
+
+/// 分镜中的一句台词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Line {
+    /// 说话人，None 表示旁白
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    pub text: String,
+    /// 该句语音在分镜内的起始时间（秒），合成语音后填入
+    #[serde(skip)]
+    pub start: f64,
+    /// 该句语音的实际时长（秒），合成语音后填入
+    #[serde(skip)]
+    pub duration: f64,
+    /// 逐字/逐词时间，合成语音后填入
+    #[serde(skip)]
+    pub words: Vec<WordTiming>,
+    /// 字幕颜色，合成前由 cast 解析
+    #[serde(skip)]
+    pub color: Option<String>,
+    /// 字幕前的说话人标签，合成前由 cast 解析
+    #[serde(skip)]
+    pub label: Option<String>,
+}
+
+impl Line {
+    pub fn new(speaker: Option<String>, text: String) -> Self {
+        Self {
+            speaker,
+            text,
+            start: 0.0,
+            duration: 0.0,
+            words: Vec::new(),
+            color: None,
+            label: None,
+        }
+    }
+}
+
+/// 分镜中的音效/环境声
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundCue {
+    /// 音效文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 音效库标签，在 --sfx-dir 中按文件名或子目录名匹配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// 相对分镜开始的偏移（秒）
+    #[serde(default)]
+    pub offset: f64,
+    /// 音量（dB）
+    #[serde(default = "SoundCue::default_volume")]
+    pub volume: f64,
+    /// 是否循环铺满分镜剩余时长
+    #[serde(default, rename = "loop")]
+    pub looped: bool,
+    /// 解析后的实际文件路径，合成前填入
+    #[serde(skip)]
+    pub resolved_path: Option<String>,
+}
+
+impl SoundCue {
+    /// 默认压在旁白之下
+    pub fn default_volume() -> f64 {
+        -12.0
+    }
+}
+
+/// 分镜使用的本地图片或视频素材，代替生成的图片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneMedia {
+    /// 素材文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 素材目录标签，在 --media-dir 中按文件名或子目录名匹配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// 视频素材的入点（秒）
+    #[serde(default)]
+    pub start: f64,
+    /// 视频短于分镜时循环播放，否则定格在最后一帧
+    #[serde(default = "SceneMedia::default_loop", rename = "loop")]
+    pub looped: bool,
+    /// 是否保留视频原声，默认静音
+    #[serde(default)]
+    pub keep_audio: bool,
+    /// 保留原声时的音量（dB）
+    #[serde(default = "SoundCue::default_volume")]
+    pub volume: f64,
+    /// 解析后的实际文件路径，合成前填入
+    #[serde(skip)]
+    pub resolved_path: Option<String>,
+    /// 是否为视频（否则为静态图片），合成前填入
+    #[serde(skip)]
+    pub is_video: bool,
+    /// 视频是否带音轨，合成前填入
+    #[serde(skip)]
+    pub has_audio: bool,
+}
+
+impl SceneMedia {
+    fn default_loop() -> bool {
+        true
+    }
+
+    /// 图生视频得到的片段，短于分镜时定格在最后一帧
+    pub fn generated_clip(path: String) -> Self {
+        Self {
+            file: Some(path.clone()),
+            tag: None,
+            start: 0.0,
+            looped: false,
+            keep_audio: false,
+            volume: SoundCue::default_volume(),
+            resolved_path: Some(path),
+            is_video: true,
+            has_audio: false,
+        }
+    }
+
+    /// 已解析的视频素材路径
+    pub fn video(&self) -> Option<&str> {
+        self.resolved_path.as_deref().filter(|_| self.is_video)
+    }
+
+    /// 需要混入的原声
+    pub fn kept_audio(&self) -> Option<&str> {
+        self.video().filter(|_| self.keep_audio && self.has_audio)
+    }
+}
+
+/// 角色设定：音色与字幕样式
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CastMember {
+    #[serde(flatten)]
+    pub voice: VoiceSettings,
+    /// 字幕颜色（FFmpeg 颜色名或 #RRGGBB）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// 字幕中显示的名字，默认使用角色名；设为空字符串则不显示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
 
 /// 表示一个场景/分镜
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +170,33 @@ pub struct Scene {
     pub image_path: Option<String>,
     /// 该场景的时长（秒）
     pub duration: f64,
+    /// 该场景的旁白音色覆盖
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<VoiceSettings>,
+    /// 该场景的文生图参数覆盖
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageOverrides>,
+    /// 出场角色名，对应设定集中的角色
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub characters: Vec<String>,
+    /// 所在场景名，对应设定集中的场景
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setting: Option<String>,
+    /// 分句台词；为空时整段 subtitle 作为一句旁白
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<Line>,
+    /// 音效与环境声
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sounds: Vec<SoundCue>,
+    /// 本地图片或视频素材，设置后不再生成图片
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<SceneMedia>,
+    /// 图生视频的运动提示词（英文），为空时使用场景描述
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motion: Option<String>,
+    /// 图生视频失败时以推拉摇移（Ken Burns）效果代替，合成前填入
+    #[serde(skip)]
+    pub ken_burns: bool,
 }
 
 impl Scene {
@@ -23,6 +207,101 @@ impl Scene {
             subtitle,
             image_path: None,
             duration,
+            voice: None,
+            image: None,
+            characters: Vec::new(),
+            setting: None,
+            lines: Vec::new(),
+            sounds: Vec::new(),
+            media: None,
+            motion: None,
+            ken_burns: false,
+        }
+    }
+
+    /// 没有分句台词时用 subtitle 补全为一句旁白
+    pub fn ensure_lines(&mut self) {
+        if self.lines.is_empty() && !self.subtitle.trim().is_empty() {
+            self.lines.push(Line::new(None, self.subtitle.clone()));
+        }
+    }
+}
+
+/// 分镜脚本，保存在工作目录的 storyboard.json 中，可手动编辑后重新合成
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Storyboard {
+    /// 生成分镜时的原始文本，用于判断脚本是否过期
+    #[serde(default)]
+    pub source_text: String,
+    /// 项目级旁白音色
+    #[serde(default)]
+    pub voice: VoiceSettings,
+    /// 角色名到音色/字幕样式的映射
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cast: BTreeMap<String, CastMember>,
+    /// 角色与场景设定集
+    #[serde(default, skip_serializing_if = "Bible::is_empty")]
+    pub bible: Bible,
+    pub scenes: Vec<Scene>,
+}
+
+impl Storyboard {
+    pub fn new(source_text: String, bible: Bible, scenes: Vec<Scene>) -> Self {
+        Self {
+            source_text,
+            voice: VoiceSettings::default(),
+            cast: BTreeMap::new(),
+            bible,
+            scenes,
+        }
+    }
+
+    /// 按出场顺序列出所有说话人
+    pub fn speakers(&self) -> Vec<String> {
+        let mut speakers: Vec<String> = Vec::new();
+        for line in self.scenes.iter().flat_map(|s| &s.lines) {
+            if let Some(speaker) = &line.speaker {
+                if !speakers.contains(speaker) {
+                    speakers.push(speaker.clone());
+                }
+            }
+        }
+        speakers
+    }
+
+    /// 为每句台词填入字幕颜色和说话人标签
+    pub fn apply_cast_styles(&mut self) {
+        let speakers = self.speakers();
+        for line in self.scenes.iter_mut().flat_map(|s| s.lines.iter_mut()) {
+            let Some(speaker) = &line.speaker else {
+                continue;
+            };
+            let member = self.cast.get(speaker);
+            let position = speakers.iter().position(|s| s == speaker).unwrap_or(0);
+
+            line.color = member
+                .and_then(|m| m.color.clone())
+                .or_else(|| Some(SPEAKER_COLORS[position % SPEAKER_COLORS.len()].to_string()));
+            line.label = match member.and_then(|m| m.label.clone()) {
+                Some(label) if label.is_empty() => None,
+                Some(label) => Some(label),
+                None => Some(speaker.clone()),
+            };
         }
     }
+
+    /// 读取分镜脚本，文件不存在时返回 None
+    pub async fn load(path: &str) -> Result<Option<Self>> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(&self, path: &str) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, content).await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,768 @@
+use crate::api::QwenClient;
+use crate::config::layers::Source;
+use crate::config::{LoadedConfig, Settings, TenantSettings};
+use crate::error::{Result, VideoError};
+use crate::progress::{Event, EventBus, Stage, StageProgress};
+use crate::server::store::{JobRecord, JobStore};
+use crate::server::tenant;
+use crate::server::webhook::{Delivery, Webhook};
+use crate::{run_generation, GenerationOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, info_span, warn, Instrument};
+
+/// 任务选项中不允许设置的配置：密钥、服务端路径与外部命令由服务端决定
+const FORBIDDEN_OPTIONS: &[&str] = &[
+    "api",
+    "project",
+    "server",
+    "presets",
+    "tenants",
+    "image.score_command",
+    "speech.lexicon",
+    "subtitle.font_file",
+    "audio.music",
+    "audio.sfx_dir",
+    "video.media_dir",
+];
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// 提交任务的请求体：输入文本与覆盖服务端配置的选项（结构同 auto-video.toml）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub text: String,
+    #[serde(default)]
+    pub options: toml::Table,
+    /// 任务结束（成功、失败或取消）时接收签名回调的地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+}
+
+/// 对外返回的任务信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    /// 提交任务的租户，未启用租户时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub status: JobStatus,
+    pub progress: StageProgress,
+    /// 提交、开始与结束时间（Unix 秒）
+    pub created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 开始运行的次数，服务重启后继续运行时递增
+    #[serde(default)]
+    pub attempts: u32,
+    /// 结束回调的投递记录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deliveries: Vec<Delivery>,
+}
+
+struct JobEntry {
+    job: Job,
+    request: JobRequest,
+    cancel: CancellationToken,
+    events: EventBus,
+}
+
+/// 提交任务失败的原因
+#[derive(Debug)]
+pub enum SubmitError {
+    Invalid(String),
+    /// 超出租户的每日任务数或并发任务数
+    QuotaExceeded(String),
+    /// 请求超出租户允许的范围（如共享 Key 未设配额、候选图片数或图生视频不允许）
+    Forbidden(String),
+    QueueFull,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitError::Invalid(message)
+            | SubmitError::QuotaExceeded(message)
+            | SubmitError::Forbidden(message) => write!(f, "{}", message),
+            SubmitError::QueueFull => write!(f, "Job queue is full"),
+        }
+    }
+}
+
+/// 任务队列：固定数量的工作协程依次取出任务，每个任务在独立的工作目录中运行 `run_generation`
+///
+/// 任务记录每次变化都写入 `JobStore`；服务重启时排队中与运行中的任务重新入队，
+/// 在原工作目录中继续运行，已完成的分镜、图片与旁白直接复用。
+/// 租户运行中的任务达到 `max_concurrent` 时，取出的任务进入该租户的等待队列，
+/// 同租户的任务结束后由同一个工作协程接着运行。
+pub struct JobQueue {
+    config: LoadedConfig,
+    jobs_dir: String,
+    store: JobStore,
+    webhook: Option<Webhook>,
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    /// 按租户等待运行的任务，只在持有 `jobs` 锁时访问
+    waiting: Mutex<HashMap<String, VecDeque<String>>>,
+    sender: mpsc::Sender<String>,
+    shutdown: CancellationToken,
+}
+
+impl JobQueue {
+    /// 创建队列，恢复已保存的任务并启动 `server.workers` 个工作协程
+    pub async fn start(config: LoadedConfig) -> Result<(Arc<Self>, Vec<JoinHandle<()>>)> {
+        let server = config.settings.server.clone();
+        let store = JobStore::new(server.jobs_dir.clone());
+        let records = store.load_all()?;
+
+        let (sender, receiver) = mpsc::channel(server.queue_size.max(1));
+        let shutdown = CancellationToken::new();
+        let mut resume = Vec::new();
+        let mut jobs = HashMap::new();
+        for mut record in records {
+            let id = record.job.id.clone();
+            if !record.job.status.is_finished() {
+                info!(
+                    "Resuming job {} from stage {:?}",
+                    id, record.job.progress.stage
+                );
+                record.job.status = JobStatus::Queued;
+                resume.push(id.clone());
+            }
+            jobs.insert(
+                id,
+                JobEntry {
+                    job: record.job,
+                    request: record.request,
+                    cancel: shutdown.child_token(),
+                    events: EventBus::default(),
+                },
+            );
+        }
+
+        let webhook = Webhook::from_settings(&server);
+        let queue = Arc::new(Self {
+            config,
+            jobs_dir: server.jobs_dir,
+            store,
+            webhook,
+            jobs: Mutex::new(jobs),
+            waiting: Mutex::default(),
+            sender,
+            shutdown,
+        });
+
+        let receiver = Arc::new(tokio::sync::Mutex::new(receiver));
+        let workers = (0..server.workers.max(1))
+            .map(|_| {
+                let queue = queue.clone();
+                let receiver = receiver.clone();
+                tokio::spawn(async move { queue.work(receiver).await })
+            })
+            .collect();
+
+        // 已结束但回调尚未送达的任务继续投递
+        let undelivered: Vec<String> = queue
+            .jobs
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|entry| entry.job.status.is_finished() && queue.undelivered(entry))
+            .map(|entry| entry.job.id.clone())
+            .collect();
+        for id in undelivered {
+            info!("Resuming webhook delivery for job {}", id);
+            queue.notify(&id);
+        }
+
+        // 工作协程已启动，恢复的任务即使超过队列容量也只是等待入队
+        for id in resume {
+            if let Some(entry) = queue.jobs.lock().unwrap().get(&id) {
+                queue.persist(entry);
+            }
+            queue.sender.send(id).await.ok();
+        }
+        Ok((queue, workers))
+    }
+
+    /// 校验选项与租户配额并加入队列
+    pub fn submit(
+        &self,
+        request: JobRequest,
+        tenant: Option<String>,
+    ) -> std::result::Result<Job, SubmitError> {
+        if request.text.trim().is_empty() {
+            return Err(SubmitError::Invalid("text must not be empty".to_string()));
+        }
+        let settings = self
+            .settings_for("validation", &request.options)
+            .map_err(|e| SubmitError::Invalid(e.to_string()))?;
+        if let Some(url) = &request.callback_url {
+            let Some(webhook) = &self.webhook else {
+                return Err(SubmitError::Invalid(
+                    "callback_url requires server.webhook_secret to be configured".to_string(),
+                ));
+            };
+            webhook.validate_url(url).map_err(SubmitError::Invalid)?;
+        }
+
+        let limits = tenant
+            .as_ref()
+            .and_then(|name| self.tenants().get_key_value(name));
+        if let Some((name, limits)) = limits {
+            tenant::check_request(name, limits, &request.options, &settings)?;
+        }
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let job = Job {
+            id: id.clone(),
+            tenant: tenant.clone(),
+            status: JobStatus::Queued,
+            progress: StageProgress::new(Stage::Queued),
+            created_at: now(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+            attempts: 0,
+            deliveries: Vec::new(),
+        };
+        let entry = JobEntry {
+            job: job.clone(),
+            request,
+            cancel: self.shutdown.child_token(),
+            events: EventBus::default(),
+        };
+        {
+            // 配额检查与登记在同一把锁内，并发提交也不会超出配额
+            let mut jobs = self.jobs.lock().unwrap();
+            if let Some((name, limits)) = limits {
+                let own = jobs
+                    .values()
+                    .map(|entry| &entry.job)
+                    .filter(|job| job.tenant.as_ref() == Some(name));
+                tenant::check_quota(name, limits, &entry.request.text, own, now())?;
+            }
+            self.persist(&entry);
+            jobs.insert(id.clone(), entry);
+        }
+
+        if self.sender.try_send(id.clone()).is_err() {
+            self.jobs.lock().unwrap().remove(&id);
+            std::fs::remove_dir_all(self.work_dir(&id)).ok();
+            return Err(SubmitError::QueueFull);
+        }
+        info!("Job {} queued", id);
+        Ok(job)
+    }
+
+    /// 服务端配置的租户
+    pub fn tenants(&self) -> &BTreeMap<String, TenantSettings> {
+        &self.config.settings.tenants
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|entry| entry.job.clone())
+    }
+
+    /// 全部任务，按提交时间排列
+    pub fn list(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.job.clone())
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs
+    }
+
+    /// 订阅任务事件，同时返回订阅时的任务快照；已结束的任务不会再有事件，返回 None
+    ///
+    /// 快照与订阅在同一把锁内取得，结束事件在更新状态时发送，订阅者不会错过结束。
+    pub fn subscribe(&self, id: &str) -> Option<(Job, Option<broadcast::Receiver<Event>>)> {
+        let jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get(id)?;
+        let receiver = (!entry.job.status.is_finished()).then(|| entry.events.subscribe());
+        Some((entry.job.clone(), receiver))
+    }
+
+    /// 服务停止时取消，用于结束事件流
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// 成片路径
+    pub fn output_path(&self, id: &str) -> String {
+        format!("{}/output.mp4", self.work_dir(id))
+    }
+
+    fn work_dir(&self, id: &str) -> String {
+        format!("{}/{}", self.jobs_dir, id)
+    }
+
+    /// 取消任务：排队中的直接标记为已取消，运行中的中断流程并取消远端任务
+    pub fn cancel(self: &Arc<Self>, id: &str) -> Option<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get_mut(id)?;
+        match entry.job.status {
+            JobStatus::Queued => {
+                entry.job.status = JobStatus::Cancelled;
+                entry.job.finished_at = Some(now());
+                self.persist(entry);
+                entry.events.emit(Event::Cancelled);
+                self.notify(id);
+                info!("Job {} cancelled before start", id);
+            }
+            JobStatus::Running => {
+                entry.cancel.cancel();
+                info!("Cancelling job {}", id);
+            }
+            _ => {}
+        }
+        Some(entry.job.clone())
+    }
+
+    /// 停止接收新任务，中断运行中的任务
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    async fn work(self: Arc<Self>, receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<String>>>) {
+        loop {
+            let id = tokio::select! {
+                id = async { receiver.lock().await.recv().await } => id,
+                _ = self.shutdown.cancelled() => None,
+            };
+            let Some(id) = id else {
+                break;
+            };
+            // 任务结束后接着运行因此放行的同租户等待任务
+            let mut next = Some(id);
+            while let Some(id) = next {
+                let span = info_span!("job", id = %id);
+                next = self.run(&id).instrument(span).await;
+            }
+        }
+    }
+
+    /// 运行任务，返回因此可以开始的同租户等待任务
+    async fn run(self: &Arc<Self>, id: &str) -> Option<String> {
+        let (request, cancel, events) = self.start_job(id)?;
+
+        // 流程事件同步更新任务进度
+        let tracker = tokio::spawn(self.clone().track(id.to_string(), events.subscribe()));
+        let result = self.execute(id, request, &cancel, events).await;
+        tracker.abort();
+        tracker.await.ok();
+
+        let mut jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get_mut(id)?;
+        // 服务停止导致的中断保持未完成状态，下次启动时继续运行
+        if result.is_err() && self.shutdown.is_cancelled() {
+            entry.job.status = JobStatus::Queued;
+            self.persist(entry);
+            info!("Job {} interrupted by shutdown, will resume on restart", id);
+            return None;
+        }
+        entry.job.finished_at = Some(now());
+        let event = match result {
+            Ok(duration) => {
+                entry.job.status = JobStatus::Succeeded;
+                entry.job.progress = StageProgress::new(Stage::Finished);
+                info!("Job {} succeeded", id);
+                Event::Finished {
+                    output: self.output_path(id),
+                    duration,
+                }
+            }
+            Err(_) if cancel.is_cancelled() => {
+                entry.job.status = JobStatus::Cancelled;
+                info!("Job {} cancelled", id);
+                Event::Cancelled
+            }
+            Err(e) => {
+                error!("Job {} failed: {}", id, e);
+                entry.job.status = JobStatus::Failed;
+                entry.job.error = Some(e.to_string());
+                Event::Failed {
+                    error: e.to_string(),
+                }
+            }
+        };
+        self.persist(entry);
+        entry.events.emit(event);
+        self.notify(id);
+
+        let tenant = entry.job.tenant.clone()?;
+        self.next_waiting(&jobs, &tenant)
+    }
+
+    /// 取出租户等待队列中下一个仍在排队的任务，等待期间被取消的任务直接跳过
+    fn next_waiting(&self, jobs: &HashMap<String, JobEntry>, tenant: &str) -> Option<String> {
+        let mut waiting = self.waiting.lock().unwrap();
+        let queue = waiting.get_mut(tenant)?;
+        while let Some(id) = queue.pop_front() {
+            if jobs
+                .get(&id)
+                .is_some_and(|entry| entry.job.status == JobStatus::Queued)
+            {
+                return Some(id);
+            }
+        }
+        None
+    }
+
+    /// 回调尚未送达且还有剩余投递次数
+    fn undelivered(&self, entry: &JobEntry) -> bool {
+        let Some(webhook) = &self.webhook else {
+            return false;
+        };
+        entry.request.callback_url.is_some()
+            && !entry.job.deliveries.iter().any(Delivery::succeeded)
+            && (entry.job.deliveries.len() as u32) < webhook.attempts()
+    }
+
+    /// 在后台投递任务结束回调，不占用工作协程
+    fn notify(self: &Arc<Self>, id: &str) {
+        if self.webhook.is_some() {
+            tokio::spawn(self.clone().deliver(id.to_string()));
+        }
+    }
+
+    /// 投递回调直至成功或用完次数，每次结果都写入任务记录；服务停止时放弃等待，重启后继续
+    async fn deliver(self: Arc<Self>, id: String) {
+        let Some(webhook) = self.webhook.clone() else {
+            return;
+        };
+        loop {
+            let (url, job) = {
+                let jobs = self.jobs.lock().unwrap();
+                let Some(entry) = jobs.get(&id) else {
+                    return;
+                };
+                if !self.undelivered(entry) {
+                    return;
+                }
+                let Some(url) = entry.request.callback_url.clone() else {
+                    return;
+                };
+                (url, entry.job.clone())
+            };
+            let attempt = job.deliveries.len() as u32 + 1;
+            let event = format!("job.{}", job.status.name());
+            let delivery = tokio::select! {
+                delivery = async {
+                    tokio::time::sleep(webhook.delay(attempt)).await;
+                    webhook.deliver(&url, &event, &job, attempt).await
+                } => delivery,
+                _ = self.shutdown.cancelled() => return,
+            };
+
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(entry) = jobs.get_mut(&id) else {
+                return;
+            };
+            entry.job.deliveries.push(delivery);
+            self.persist(entry);
+            if self.undelivered(entry) {
+                continue;
+            }
+            if !entry.job.deliveries.iter().any(Delivery::succeeded) {
+                error!(
+                    "Giving up webhook for job {} after {} attempts",
+                    id,
+                    entry.job.deliveries.len()
+                );
+            }
+            return;
+        }
+    }
+
+    /// 按流程事件更新任务进度并保存
+    async fn track(self: Arc<Self>, id: String, mut receiver: broadcast::Receiver<Event>) {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let mut jobs = self.jobs.lock().unwrap();
+            if let Some(entry) = jobs.get_mut(&id) {
+                let progress = entry.job.progress;
+                entry.job.progress.apply(&event);
+                if entry.job.progress != progress {
+                    self.persist(entry);
+                }
+            }
+        }
+    }
+
+    /// 把排队中的任务标记为运行中；已取消的任务返回 None，租户已达并发上限时放入等待队列并返回 None
+    fn start_job(&self, id: &str) -> Option<(JobRequest, CancellationToken, EventBus)> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = &jobs.get(id)?.job;
+        if job.status != JobStatus::Queued {
+            return None;
+        }
+        if let Some((name, limits)) = job
+            .tenant
+            .as_ref()
+            .and_then(|name| self.tenants().get_key_value(name))
+        {
+            let own = jobs
+                .values()
+                .map(|entry| &entry.job)
+                .filter(|job| job.tenant.as_ref() == Some(name));
+            if !tenant::may_start(limits, own) {
+                info!(
+                    "Job {} waits for a running job of tenant {} to finish",
+                    id, name
+                );
+                self.waiting
+                    .lock()
+                    .unwrap()
+                    .entry(name.clone())
+                    .or_default()
+                    .push_back(id.to_string());
+                return None;
+            }
+        }
+
+        let entry = jobs.get_mut(id)?;
+        entry.job.status = JobStatus::Running;
+        entry.job.started_at = Some(now());
+        entry.job.attempts += 1;
+        self.persist(entry);
+        Some((
+            entry.request.clone(),
+            entry.cancel.clone(),
+            entry.events.clone(),
+        ))
+    }
+
+    /// 保存任务记录，写入失败只记录警告，不影响任务运行
+    fn persist(&self, entry: &JobEntry) {
+        let record = JobRecord {
+            job: entry.job.clone(),
+            request: entry.request.clone(),
+            work_dir: self.work_dir(&entry.job.id),
+            output: self.output_path(&entry.job.id),
+        };
+        if let Err(e) = self.store.save(&record) {
+            warn!("Failed to save job {}: {}", entry.job.id, e);
+        }
+    }
+
+    async fn execute(
+        self: &Arc<Self>,
+        id: &str,
+        request: JobRequest,
+        cancel: &CancellationToken,
+        events: EventBus,
+    ) -> Result<f64> {
+        let mut settings = self.settings_for(id, &request.options)?;
+        settings.project.work_dir = self.work_dir(id);
+        settings.project.output = self.output_path(id);
+        tokio::fs::create_dir_all(&settings.project.work_dir).await?;
+
+        // 租户配置在运行时读取，重启后恢复的任务使用当前配置
+        let mut max_scenes = None;
+        if let Some(name) = self.get(id).and_then(|job| job.tenant) {
+            let tenant = self.tenants().get(&name).ok_or_else(|| {
+                VideoError::Config(format!("Tenant {} is no longer configured", name))
+            })?;
+            tenant::check_request(&name, tenant, &request.options, &settings)
+                .map_err(|e| VideoError::Config(e.to_string()))?;
+            if let Some(key) = &tenant.api_key {
+                settings.api.key = Some(key.clone());
+            }
+            max_scenes = tenant.max_scenes;
+        }
+
+        let api_key =
+            settings.api.key.clone().ok_or_else(|| {
+                VideoError::Config("DASHSCOPE_API_KEY is not configured".to_string())
+            })?;
+        let client = QwenClient::new(api_key)
+            .with_options(settings.qwen_options())
+            .with_events(events.clone());
+
+        let mut options = GenerationOptions::new(&settings, false);
+        options.events = events;
+        options.max_scenes = max_scenes;
+
+        info!("Job {} started in {}", id, settings.project.work_dir);
+        tokio::select! {
+            result = run_generation(request.text, &client, options) => {
+                result.map(|summary| summary.duration)
+            }
+            _ = cancel.cancelled() => {
+                warn!("Job {} interrupted, cancelling pending tasks...", id);
+                client.tasks().cancel_pending().await;
+                Err(VideoError::Api("Job cancelled".to_string()))
+            }
+        }
+    }
+
+    /// 服务端配置叠加任务选项后的配置
+    fn settings_for(&self, id: &str, options: &toml::Table) -> Result<Settings> {
+        for key in flatten_keys(options) {
+            if FORBIDDEN_OPTIONS
+                .iter()
+                .any(|forbidden| key == *forbidden || key.starts_with(&format!("{}.", forbidden)))
+            {
+                return Err(VideoError::Config(format!(
+                    "Option {} cannot be set per job",
+                    key
+                )));
+            }
+        }
+        Ok(self
+            .config
+            .layered(options.clone(), Source::Job(id.to_string()))?
+            .settings)
+    }
+}
+
+/// 选项中的全部键，形如 section.key
+pub(crate) fn flatten_keys(options: &toml::Table) -> Vec<String> {
+    options
+        .iter()
+        .flat_map(|(section, value)| match value {
+            toml::Value::Table(table) if !table.is_empty() => table
+                .keys()
+                .map(|key| format!("{}.{}", section, key))
+                .collect(),
+            _ => vec![section.clone()],
+        })
+        .collect()
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::CliOverrides;
+    use std::path::PathBuf;
+
+    async fn queue(jobs_dir: &str) -> Arc<JobQueue> {
+        let file: toml::Table = format!(
+            "[server]\njobs_dir = {:?}\nworkers = 1\n\n\
+             [tenants.cms]\ntoken = \"cms-token\"\napi_key = \"sk-cms\"\nmax_concurrent = 1\n",
+            jobs_dir
+        )
+        .parse()
+        .unwrap();
+        let config = LoadedConfig::assemble(
+            vec![(PathBuf::from("test.toml"), file)],
+            Vec::new(),
+            CliOverrides::default(),
+        )
+        .unwrap();
+        JobQueue::start(config).await.unwrap().0
+    }
+
+    /// 直接登记任务而不送入通道，由测试自己决定何时开始
+    fn add(queue: &JobQueue, id: &str) {
+        let entry = JobEntry {
+            job: Job {
+                id: id.to_string(),
+                tenant: Some("cms".to_string()),
+                status: JobStatus::Queued,
+                progress: StageProgress::new(Stage::Queued),
+                created_at: now(),
+                started_at: None,
+                finished_at: None,
+                error: None,
+                attempts: 0,
+                deliveries: Vec::new(),
+            },
+            request: JobRequest {
+                text: "text".to_string(),
+                options: toml::Table::new(),
+                callback_url: None,
+            },
+            cancel: queue.shutdown.child_token(),
+            events: EventBus::default(),
+        };
+        queue.jobs.lock().unwrap().insert(id.to_string(), entry);
+    }
+
+    fn status(queue: &JobQueue, id: &str) -> JobStatus {
+        queue.get(id).unwrap().status
+    }
+
+    #[tokio::test]
+    async fn jobs_over_the_tenant_concurrency_limit_wait() {
+        let jobs_dir =
+            std::env::temp_dir().join(format!("auto-video-jobs-{}", uuid::Uuid::new_v4().simple()));
+        let queue = queue(&jobs_dir.to_string_lossy()).await;
+        for id in ["a", "b", "c"] {
+            add(&queue, id);
+        }
+
+        assert!(queue.start_job("a").is_some());
+        assert!(queue.start_job("b").is_none());
+        assert!(queue.start_job("c").is_none());
+        assert_eq!(status(&queue, "b"), JobStatus::Queued);
+
+        // 等待中被取消的任务不再运行
+        queue.cancel("b");
+        queue.jobs.lock().unwrap().get_mut("a").unwrap().job.status = JobStatus::Succeeded;
+        let next = queue.next_waiting(&queue.jobs.lock().unwrap(), "cms");
+        assert_eq!(next.as_deref(), Some("c"));
+        assert!(queue.start_job("c").is_some());
+        assert_eq!(status(&queue, "c"), JobStatus::Running);
+
+        queue.shutdown();
+        std::fs::remove_dir_all(&jobs_dir).ok();
+    }
+}
@@ -0,0 +1,247 @@
+pub mod job;
+pub mod store;
+pub mod tenant;
+pub mod webhook;
+
+pub use job::{Job, JobQueue, JobRequest, JobStatus, SubmitError};
+
+use crate::config::LoadedConfig;
+use crate::error::Result;
+use crate::progress::Event;
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRequestParts, Path, State};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::sse::{self, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures::{stream, Stream, StreamExt};
+use serde_json::json;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_util::io::ReaderStream;
+use tracing::info;
+
+/// 接口错误，以 `{"error": "..."}` 返回
+struct HttpError(StatusCode, String);
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type HttpResult<T> = std::result::Result<T, HttpError>;
+
+fn not_found(id: &str) -> HttpError {
+    HttpError(StatusCode::NOT_FOUND, format!("Job {} not found", id))
+}
+
+/// 调用方：配置了租户时为令牌对应的租户名，否则为 None，不校验令牌
+struct Caller(Option<String>);
+
+impl Caller {
+    /// 租户只能看到自己的任务，其他租户的任务视同不存在
+    fn can_access(&self, job: &Job) -> bool {
+        self.0.is_none() || job.tenant == self.0
+    }
+
+    /// 查找调用方可见的任务
+    fn job(&self, queue: &JobQueue, id: &str) -> HttpResult<Job> {
+        queue
+            .get(id)
+            .filter(|job| self.can_access(job))
+            .ok_or_else(|| not_found(id))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<JobQueue>> for Caller {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, queue: &Arc<JobQueue>) -> HttpResult<Self> {
+        let tenants = queue.tenants();
+        if tenants.is_empty() {
+            return Ok(Caller(None));
+        }
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or_else(|| {
+                HttpError(StatusCode::UNAUTHORIZED, "Missing bearer token".to_string())
+            })?;
+        tenant::authenticate(tenants, token)
+            .map(|name| Caller(Some(name.to_string())))
+            .ok_or_else(|| HttpError(StatusCode::UNAUTHORIZED, "Invalid token".to_string()))
+    }
+}
+
+/// 运行 HTTP 服务，Ctrl-C 时停止接收请求并中断运行中的任务
+pub async fn serve(config: LoadedConfig) -> Result<()> {
+    tenant::validate(&config.settings)?;
+    let server = config.settings.server.clone();
+    tokio::fs::create_dir_all(&server.jobs_dir).await?;
+
+    let (queue, workers) = JobQueue::start(config).await?;
+    let app = Router::new()
+        .route("/jobs", get(list_jobs).post(create_job))
+        .route("/jobs/:id", get(get_job).delete(cancel_job))
+        .route("/jobs/:id/output", get(download_output))
+        .route("/jobs/:id/events", get(job_events))
+        .with_state(queue.clone());
+
+    let listener = tokio::net::TcpListener::bind(&server.bind).await?;
+    info!(
+        "Serving on http://{} with {} workers, jobs in {}",
+        server.bind, server.workers, server.jobs_dir
+    );
+    // 先中断任务并关闭事件流，否则优雅停机会一直等待订阅中的连接
+    let stopping = queue.clone();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            tokio::signal::ctrl_c().await.ok();
+            info!("Shutting down, interrupting running jobs...");
+            stopping.shutdown();
+        })
+        .await?;
+
+    for worker in workers {
+        worker.await.ok();
+    }
+    Ok(())
+}
+
+async fn create_job(
+    State(queue): State<Arc<JobQueue>>,
+    caller: Caller,
+    Json(request): Json<JobRequest>,
+) -> HttpResult<(StatusCode, Json<Job>)> {
+    match queue.submit(request, caller.0) {
+        Ok(job) => Ok((StatusCode::ACCEPTED, Json(job))),
+        Err(SubmitError::Invalid(message)) => Err(HttpError(StatusCode::BAD_REQUEST, message)),
+        Err(SubmitError::QuotaExceeded(message)) => {
+            Err(HttpError(StatusCode::TOO_MANY_REQUESTS, message))
+        }
+        Err(SubmitError::Forbidden(message)) => Err(HttpError(StatusCode::FORBIDDEN, message)),
+        Err(SubmitError::QueueFull) => Err(HttpError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Job queue is full, try again later".to_string(),
+        )),
+    }
+}
+
+async fn list_jobs(State(queue): State<Arc<JobQueue>>, caller: Caller) -> Json<Vec<Job>> {
+    Json(
+        queue
+            .list()
+            .into_iter()
+            .filter(|job| caller.can_access(job))
+            .collect(),
+    )
+}
+
+async fn get_job(
+    State(queue): State<Arc<JobQueue>>,
+    caller: Caller,
+    Path(id): Path<String>,
+) -> HttpResult<Json<Job>> {
+    caller.job(&queue, &id).map(Json)
+}
+
+async fn cancel_job(
+    State(queue): State<Arc<JobQueue>>,
+    caller: Caller,
+    Path(id): Path<String>,
+) -> HttpResult<Json<Job>> {
+    caller.job(&queue, &id)?;
+    let job = queue.cancel(&id).ok_or_else(|| not_found(&id))?;
+    if job.status.is_finished() && job.status != JobStatus::Cancelled {
+        return Err(HttpError(
+            StatusCode::CONFLICT,
+            format!("Job {} already finished", id),
+        ));
+    }
+    Ok(Json(job))
+}
+
+async fn download_output(
+    State(queue): State<Arc<JobQueue>>,
+    caller: Caller,
+    Path(id): Path<String>,
+) -> HttpResult<Response> {
+    let job = caller.job(&queue, &id)?;
+    if job.status != JobStatus::Succeeded {
+        return Err(HttpError(
+            StatusCode::CONFLICT,
+            format!("Job {} is {}, no output yet", id, job.status.name()),
+        ));
+    }
+
+    let file = tokio::fs::File::open(queue.output_path(&id))
+        .await
+        .map_err(|e| HttpError(StatusCode::GONE, format!("Output unavailable: {}", e)))?;
+    let length = file.metadata().await.map(|m| m.len()).ok();
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, "video/mp4")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.mp4\"", id),
+        );
+    if let Some(length) = length {
+        response = response.header(header::CONTENT_LENGTH, length);
+    }
+    response
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| HttpError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// 任务事件流（SSE）：先发送一条 `status` 任务快照，再转发流程事件，任务结束或服务停止时关闭
+async fn job_events(
+    State(queue): State<Arc<JobQueue>>,
+    caller: Caller,
+    Path(id): Path<String>,
+) -> HttpResult<Sse<impl Stream<Item = std::result::Result<sse::Event, Infallible>>>> {
+    let (job, receiver) = queue
+        .subscribe(&id)
+        .filter(|(job, _)| caller.can_access(job))
+        .ok_or_else(|| not_found(&id))?;
+    let snapshot = sse::Event::default()
+        .event("status")
+        .json_data(&job)
+        .map_err(|e| HttpError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let events = stream::unfold(receiver, next_event).filter_map(|event| async move {
+        sse::Event::default()
+            .event(event.name())
+            .json_data(&event)
+            .ok()
+    });
+    let stream = stream::once(async { snapshot })
+        .chain(events)
+        .map(Ok)
+        .take_until(queue.shutdown_token().cancelled_owned());
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// 取下一个事件，结束事件之后不再继续；订阅者落后时以警告事件说明丢失的数量
+async fn next_event(
+    receiver: Option<broadcast::Receiver<Event>>,
+) -> Option<(Event, Option<broadcast::Receiver<Event>>)> {
+    let mut receiver = receiver?;
+    let event = match receiver.recv().await {
+        Ok(event) => event,
+        Err(broadcast::error::RecvError::Lagged(skipped)) => Event::Warning {
+            message: format!("{} events dropped, client is too slow", skipped),
+        },
+        Err(broadcast::error::RecvError::Closed) => return None,
+    };
+    let next = (!event.is_terminal()).then_some(receiver);
+    Some((event, next))
+}
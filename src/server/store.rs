@@ -0,0 +1,68 @@
+use crate::error::Result;
+use crate::server::job::{Job, JobRequest};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::warn;
+
+/// 任务目录中保存任务记录的文件名
+pub const JOB_FILE: &str = "job.json";
+
+/// 持久化的任务记录：状态、请求与产物路径，服务重启后据此恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    #[serde(flatten)]
+    pub job: Job,
+    pub request: JobRequest,
+    pub work_dir: String,
+    pub output: String,
+}
+
+/// 基于文件的任务存储：每个任务的记录保存在各自工作目录的 job.json 中
+#[derive(Debug, Clone)]
+pub struct JobStore {
+    dir: String,
+}
+
+impl JobStore {
+    pub fn new(dir: impl Into<String>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 写入任务记录：先写临时文件再重命名，进程中途退出也不会留下半个文件
+    pub fn save(&self, record: &JobRecord) -> Result<()> {
+        let dir = Path::new(&self.dir).join(&record.job.id);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(JOB_FILE);
+        let temp = dir.join(format!("{}.tmp", JOB_FILE));
+        std::fs::write(&temp, serde_json::to_string_pretty(record)?)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    /// 读取全部任务记录，无法解析的记录会被跳过
+    pub fn load_all(&self) -> Result<Vec<JobRecord>> {
+        let mut records = Vec::new();
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(records),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path().join(JOB_FILE);
+            if !path.is_file() {
+                continue;
+            }
+            let record = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    serde_json::from_str::<JobRecord>(&content).map_err(|e| e.to_string())
+                });
+            match record {
+                Ok(record) => records.push(record),
+                Err(e) => warn!("Skipping unreadable job record {}: {}", path.display(), e),
+            }
+        }
+        records.sort_by_key(|record| record.job.created_at);
+        Ok(records)
+    }
+}
@@ -0,0 +1,337 @@
+use crate::config::{Settings, TenantSettings};
+use crate::error::{Result, VideoError};
+use crate::server::job::{self, Job, JobStatus, SubmitError};
+use std::collections::{BTreeMap, HashSet};
+use tracing::warn;
+
+/// 一天的秒数，每日配额按 UTC 自然日计算
+const DAY: u64 = 24 * 60 * 60;
+
+/// 使用共享 Key 的租户不能按任务修改的选项：它们决定图生视频的单价与时长
+const SHARED_KEY_FORBIDDEN_OPTIONS: &[&str] = &[
+    "animation.model",
+    "animation.resolution",
+    "animation.duration",
+];
+
+/// 按访问令牌查找租户，逐个做常量时间比较
+pub fn authenticate<'a>(
+    tenants: &'a BTreeMap<String, TenantSettings>,
+    token: &str,
+) -> Option<&'a str> {
+    tenants
+        .iter()
+        .find(|(_, tenant)| {
+            tenant.token.len() == token.len()
+                && openssl::memcmp::eq(tenant.token.as_bytes(), token.as_bytes())
+        })
+        .map(|(name, _)| name.as_str())
+}
+
+/// 启动服务前校验租户配置：令牌非空且互不相同；存在使用共享 Key 的调用方时必须配置 api.key
+pub fn validate(settings: &Settings) -> Result<()> {
+    let mut tokens = HashSet::new();
+    for (name, tenant) in &settings.tenants {
+        if tenant.token.trim().is_empty() {
+            return Err(VideoError::Config(format!(
+                "Tenant {} has an empty token",
+                name
+            )));
+        }
+        if !tokens.insert(tenant.token.as_str()) {
+            return Err(VideoError::Config(format!(
+                "Tenant {} reuses another tenant's token",
+                name
+            )));
+        }
+        if tenant.api_key.is_none() && !bounded(tenant) {
+            warn!(
+                "Tenant {} uses the shared api.key without jobs_per_day and max_scenes, its jobs will be refused",
+                name
+            );
+        }
+    }
+
+    let shared = settings.tenants.is_empty()
+        || settings
+            .tenants
+            .values()
+            .any(|tenant| tenant.api_key.is_none());
+    if shared && settings.api.key.is_none() {
+        return Err(VideoError::Config(
+            "DASHSCOPE_API_KEY not found. Set it via --api-key, DASHSCOPE_API_KEY or api.key in auto-video.toml, or give every tenant an api_key".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 共享 Key 的费用上限由每日任务数与每个任务的分镜数共同决定
+fn bounded(tenant: &TenantSettings) -> bool {
+    tenant.jobs_per_day.is_some() && tenant.max_scenes.is_some()
+}
+
+/// 提交前检查请求是否在租户允许的范围内，`settings` 为叠加任务选项后的配置
+///
+/// 使用共享 Key 的租户必须设置每日任务数与分镜数上限，且不能修改图生视频的计费参数；
+/// 候选图片数与是否允许图生视频对所有租户生效。
+pub fn check_request(
+    name: &str,
+    tenant: &TenantSettings,
+    options: &toml::Table,
+    settings: &Settings,
+) -> std::result::Result<(), SubmitError> {
+    if tenant.api_key.is_none() {
+        if !bounded(tenant) {
+            return Err(SubmitError::Forbidden(format!(
+                "Tenant {} uses the shared DashScope key but has no jobs_per_day and max_scenes quota",
+                name
+            )));
+        }
+        if let Some(key) = job::flatten_keys(options)
+            .into_iter()
+            .find(|key| SHARED_KEY_FORBIDDEN_OPTIONS.contains(&key.as_str()))
+        {
+            return Err(SubmitError::Forbidden(format!(
+                "Option {} cannot be set by tenant {} on the shared DashScope key",
+                key, name
+            )));
+        }
+    }
+
+    if let Some(max) = tenant.max_candidates {
+        if settings.image.n > max {
+            return Err(SubmitError::Forbidden(format!(
+                "Tenant {} allows at most {} candidate images per scene, requested {}",
+                name, max, settings.image.n
+            )));
+        }
+    }
+    if settings.animation.enabled && !tenant.allow_animation {
+        return Err(SubmitError::Forbidden(format!(
+            "Tenant {} is not allowed to animate scenes",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// 提交前检查租户配额：文本长度与当日任务数，`jobs` 为该租户的全部任务
+///
+/// `max_concurrent` 不在提交时检查，超出的任务在队列中等待，见 `may_start`。
+pub fn check_quota<'a>(
+    name: &str,
+    tenant: &TenantSettings,
+    text: &str,
+    jobs: impl Iterator<Item = &'a Job>,
+    now: u64,
+) -> std::result::Result<(), SubmitError> {
+    if let Some(max) = tenant.max_text_length {
+        let length = text.chars().count();
+        if length > max {
+            return Err(SubmitError::Invalid(format!(
+                "Text has {} characters, tenant {} allows at most {}",
+                length, name, max
+            )));
+        }
+    }
+
+    let today = now - now % DAY;
+    let submitted = jobs.filter(|job| job.created_at >= today).count();
+    if let Some(max) = tenant.jobs_per_day {
+        if submitted >= max {
+            return Err(SubmitError::QuotaExceeded(format!(
+                "Tenant {} has used its quota of {} jobs today",
+                name, max
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 租户运行中的任务数是否低于 `max_concurrent`，`jobs` 为该租户的全部任务
+pub fn may_start<'a>(tenant: &TenantSettings, jobs: impl Iterator<Item = &'a Job>) -> bool {
+    match tenant.max_concurrent {
+        Some(max) => jobs.filter(|job| job.status == JobStatus::Running).count() < max,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::{Stage, StageProgress};
+
+    fn shared(jobs_per_day: Option<usize>, max_scenes: Option<usize>) -> TenantSettings {
+        TenantSettings {
+            token: "token".to_string(),
+            api_key: None,
+            jobs_per_day,
+            max_scenes,
+            max_text_length: None,
+            max_concurrent: None,
+            max_candidates: None,
+            allow_animation: false,
+        }
+    }
+
+    fn job(created_at: u64, status: JobStatus) -> Job {
+        Job {
+            id: created_at.to_string(),
+            tenant: Some("cms".to_string()),
+            status,
+            progress: StageProgress::new(Stage::Queued),
+            created_at,
+            started_at: None,
+            finished_at: None,
+            error: None,
+            attempts: 0,
+            deliveries: Vec::new(),
+        }
+    }
+
+    fn options(toml: &str) -> toml::Table {
+        toml.parse().unwrap()
+    }
+
+    fn forbidden(result: std::result::Result<(), SubmitError>) -> bool {
+        matches!(result, Err(SubmitError::Forbidden(_)))
+    }
+
+    #[test]
+    fn authenticate_matches_exact_token() {
+        let mut tenants = BTreeMap::new();
+        tenants.insert("cms".to_string(), shared(None, None));
+        assert_eq!(authenticate(&tenants, "token"), Some("cms"));
+        assert_eq!(authenticate(&tenants, "toke"), None);
+        assert_eq!(authenticate(&tenants, "tokenx"), None);
+    }
+
+    #[test]
+    fn shared_key_tenants_need_bounding_quotas() {
+        let settings = Settings::default();
+        let empty = toml::Table::new();
+        assert!(forbidden(check_request(
+            "cms",
+            &shared(None, Some(8)),
+            &empty,
+            &settings
+        )));
+        assert!(forbidden(check_request(
+            "cms",
+            &shared(Some(10), None),
+            &empty,
+            &settings
+        )));
+        assert!(check_request("cms", &shared(Some(10), Some(8)), &empty, &settings).is_ok());
+
+        let own = TenantSettings {
+            api_key: Some("sk-own".to_string()),
+            ..shared(None, None)
+        };
+        assert!(check_request("cms", &own, &empty, &settings).is_ok());
+    }
+
+    #[test]
+    fn shared_key_tenants_cannot_change_animation_pricing() {
+        let tenant = TenantSettings {
+            allow_animation: true,
+            ..shared(Some(10), Some(8))
+        };
+        let settings = Settings::default();
+        let request = options("[animation]\nduration = 10\n");
+        assert!(forbidden(check_request(
+            "cms", &tenant, &request, &settings
+        )));
+
+        let own = TenantSettings {
+            api_key: Some("sk-own".to_string()),
+            ..tenant
+        };
+        assert!(check_request("cms", &own, &request, &settings).is_ok());
+    }
+
+    #[test]
+    fn candidates_and_animation_follow_tenant_caps() {
+        let tenant = TenantSettings {
+            max_candidates: Some(2),
+            ..shared(Some(10), Some(8))
+        };
+        let mut settings = Settings::default();
+        settings.image.n = 3;
+        assert!(forbidden(check_request(
+            "cms",
+            &tenant,
+            &toml::Table::new(),
+            &settings
+        )));
+        settings.image.n = 2;
+        assert!(check_request("cms", &tenant, &toml::Table::new(), &settings).is_ok());
+
+        settings.animation.enabled = true;
+        assert!(forbidden(check_request(
+            "cms",
+            &tenant,
+            &toml::Table::new(),
+            &settings
+        )));
+        let tenant = TenantSettings {
+            allow_animation: true,
+            ..tenant
+        };
+        assert!(check_request("cms", &tenant, &toml::Table::new(), &settings).is_ok());
+    }
+
+    #[test]
+    fn text_length_is_counted_in_characters() {
+        let tenant = TenantSettings {
+            max_text_length: Some(4),
+            ..shared(None, None)
+        };
+        assert!(check_quota("cms", &tenant, "春天来了", std::iter::empty(), 0).is_ok());
+        assert!(matches!(
+            check_quota("cms", &tenant, "春天来了。", std::iter::empty(), 0),
+            Err(SubmitError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn daily_quota_resets_at_utc_midnight() {
+        let tenant = shared(Some(2), None);
+        let now = 10 * DAY + 3600;
+        let jobs = [
+            job(10 * DAY - 1, JobStatus::Succeeded),
+            job(10 * DAY, JobStatus::Succeeded),
+        ];
+        assert!(check_quota("cms", &tenant, "text", jobs.iter(), now).is_ok());
+
+        let jobs = [
+            job(10 * DAY, JobStatus::Succeeded),
+            job(10 * DAY + 60, JobStatus::Failed),
+        ];
+        assert!(matches!(
+            check_quota("cms", &tenant, "text", jobs.iter(), now),
+            Err(SubmitError::QuotaExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn concurrency_limits_running_jobs_not_submissions() {
+        let tenant = TenantSettings {
+            max_concurrent: Some(2),
+            ..shared(None, None)
+        };
+        // 排队中的任务不计入，超出的任务提交后在队列中等待
+        let jobs = [
+            job(1, JobStatus::Running),
+            job(2, JobStatus::Queued),
+            job(3, JobStatus::Queued),
+            job(4, JobStatus::Succeeded),
+        ];
+        assert!(check_quota("cms", &tenant, "text", jobs.iter(), 10).is_ok());
+        assert!(may_start(&tenant, jobs.iter()));
+
+        let jobs = [job(1, JobStatus::Running), job(2, JobStatus::Running)];
+        assert!(!may_start(&tenant, jobs.iter()));
+        assert!(may_start(&shared(None, None), jobs.iter()));
+    }
+}
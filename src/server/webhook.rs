@@ -0,0 +1,501 @@
+use crate::config::ServerSettings;
+use crate::error::{Result, VideoError};
+use crate::server::job::Job;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use reqwest::redirect::Policy;
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 签名请求头，值为 `sha256=<十六进制 HMAC>`
+pub const SIGNATURE_HEADER: &str = "X-AutoVideo-Signature";
+/// 签名时间戳请求头（Unix 秒），参与签名，接收方可据此拒绝过期的重放请求
+pub const TIMESTAMP_HEADER: &str = "X-AutoVideo-Timestamp";
+/// 事件类型请求头，与载荷中的 `event` 相同
+pub const EVENT_HEADER: &str = "X-AutoVideo-Event";
+
+/// 一次回调投递的结果，记录在任务中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    /// 第几次投递，从 1 开始
+    pub attempt: u32,
+    /// 投递时间（Unix 秒）
+    pub at: u64,
+    /// 接收方返回的 HTTP 状态码，连接失败时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Delivery {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// 回调载荷
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    event: &'a str,
+    job: &'a Job,
+}
+
+/// 任务结束回调：向任务的 `callback_url` POST 带 HMAC-SHA256 签名的 JSON，失败时按倍增间隔重试
+///
+/// 回调地址由调用方提供，默认只投递到公网地址：每次投递前解析主机名并检查全部地址，
+/// 连接固定到检查过的地址，且不跟随重定向，避免借回调访问服务端所在的内网。
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    secret: String,
+    attempts: u32,
+    retry_interval: f64,
+    timeout: Duration,
+    allow_private: bool,
+}
+
+impl Webhook {
+    /// 未配置 `server.webhook_secret` 时返回 None，此时不接受带回调地址的任务
+    pub fn from_settings(settings: &ServerSettings) -> Option<Self> {
+        let secret = settings.webhook_secret.clone()?;
+        Some(Self {
+            secret,
+            attempts: settings.webhook_attempts.max(1),
+            retry_interval: settings.webhook_retry_interval.max(0.0),
+            timeout: Duration::from_secs_f64(settings.webhook_timeout.max(1.0)),
+            allow_private: settings.allow_private_callbacks,
+        })
+    }
+
+    /// 提交时校验回调地址：只接受 http/https；主机为 IP 时直接检查是否为内网地址，
+    /// 域名在每次投递前解析后再检查
+    pub fn validate_url(&self, url: &str) -> std::result::Result<(), String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid callback_url {}: {}", url, e))?;
+        match parsed.scheme() {
+            "http" | "https" => {}
+            scheme => {
+                return Err(format!(
+                    "callback_url must use http or https, got {}",
+                    scheme
+                ))
+            }
+        }
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| format!("callback_url {} has no host", url))?;
+        if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            self.check_address(ip)?;
+        }
+        Ok(())
+    }
+
+    fn check_address(&self, ip: IpAddr) -> std::result::Result<(), String> {
+        if self.allow_private || is_public(ip) {
+            Ok(())
+        } else {
+            Err(format!(
+                "callback_url resolves to non-public address {} (set server.allow_private_callbacks to allow)",
+                ip
+            ))
+        }
+    }
+
+    /// 解析回调主机并检查全部地址，返回连接固定到这些地址、不跟随重定向的客户端
+    async fn client_for(&self, url: &Url) -> std::result::Result<Client, String> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("callback_url {} has no host", url))?;
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+            .collect();
+        if addrs.is_empty() {
+            return Err(format!("{} did not resolve to any address", host));
+        }
+        for addr in &addrs {
+            self.check_address(addr.ip())?;
+        }
+        Client::builder()
+            .timeout(self.timeout)
+            .redirect(Policy::none())
+            .resolve_to_addrs(host, &addrs)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    /// 最多投递次数
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// 第 `attempt` 次投递前的等待时间：首次立即投递，之后从 `webhook_retry_interval` 起倍增
+    pub fn delay(&self, attempt: u32) -> Duration {
+        if attempt <= 1 {
+            return Duration::ZERO;
+        }
+        let factor = 2f64.powi(attempt.saturating_sub(2).min(16) as i32);
+        Duration::from_secs_f64(self.retry_interval * factor)
+    }
+
+    /// 投递一次，返回投递记录；接收方返回 2xx 视为成功
+    pub async fn deliver(&self, url: &str, event: &str, job: &Job, attempt: u32) -> Delivery {
+        let at = super::job::now();
+        let (status, error) = match self.send(url, event, job, at).await {
+            Ok(status) if status.is_success() => (Some(status.as_u16()), None),
+            Ok(status) => (
+                Some(status.as_u16()),
+                Some(format!("Receiver returned {}", status)),
+            ),
+            Err(e) => (None, Some(e)),
+        };
+        let delivery = Delivery {
+            attempt,
+            at,
+            status,
+            error,
+        };
+
+        match &delivery.error {
+            None => info!("Delivered {} for job {} to {}", event, job.id, url),
+            Some(e) => warn!(
+                "Webhook attempt {}/{} for job {} failed: {}",
+                attempt, self.attempts, job.id, e
+            ),
+        }
+        delivery
+    }
+
+    async fn send(
+        &self,
+        url: &str,
+        event: &str,
+        job: &Job,
+        at: u64,
+    ) -> std::result::Result<StatusCode, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid callback_url {}: {}", url, e))?;
+        let body = serde_json::to_vec(&Payload { event, job }).map_err(|e| e.to_string())?;
+        let signature = sign(&self.secret, at, &body).map_err(|e| e.to_string())?;
+        let client = self.client_for(&parsed).await?;
+
+        let response = client
+            .post(parsed)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+            .header(TIMESTAMP_HEADER, at.to_string())
+            .header(EVENT_HEADER, event)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(response.status())
+    }
+}
+
+/// 对 `{时间戳}.{请求体}` 计算 HMAC-SHA256，返回小写十六进制
+pub fn sign(secret: &str, timestamp: u64, body: &[u8]) -> Result<String> {
+    let key = PKey::hmac(secret.as_bytes()).map_err(signing_error)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key).map_err(signing_error)?;
+    signer
+        .update(format!("{}.", timestamp).as_bytes())
+        .map_err(signing_error)?;
+    signer.update(body).map_err(signing_error)?;
+    let mac = signer.sign_to_vec().map_err(signing_error)?;
+    Ok(mac.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn signing_error(e: openssl::error::ErrorStack) -> VideoError {
+    VideoError::Config(format!("Failed to sign webhook payload: {}", e))
+}
+
+/// 是否为公网地址：回环、私有网段、链路本地、运营商 NAT、组播及保留地址都不算
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && c == 0)
+                || (a == 198 && (b == 18 || b == 19)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // 唯一本地地址 fc00::/7 与链路本地地址 fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::CliOverrides;
+    use crate::config::LoadedConfig;
+    use crate::progress::{Stage, StageProgress};
+    use crate::server::job::{JobQueue, JobRequest, JobStatus};
+    use crate::server::store::{JobRecord, JobStore, JOB_FILE};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 接收方收到的一次请求
+    #[derive(Debug, Clone)]
+    struct Received {
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+        at: Instant,
+    }
+
+    /// 在 127.0.0.1 上启动接收方，依次以 `statuses` 中的状态码响应（用完后一直返回最后一个）
+    async fn receiver(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let request = read_request(&mut stream).await;
+                let count = {
+                    let mut log = log.lock().unwrap();
+                    log.push(request);
+                    log.len()
+                };
+                let status = statuses[(count - 1).min(statuses.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.ok();
+            }
+        });
+        (url, received)
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> Received {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let header_end = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            assert!(n > 0, "connection closed before headers");
+        };
+        let headers: HashMap<String, String> = String::from_utf8_lossy(&data[..header_end])
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        let length: usize = headers
+            .get("content-length")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        while data.len() < header_end + length {
+            let n = stream.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed before body");
+            data.extend_from_slice(&buf[..n]);
+        }
+        Received {
+            headers,
+            body: data[header_end..header_end + length].to_vec(),
+            at: Instant::now(),
+        }
+    }
+
+    fn settings(allow_private: bool) -> ServerSettings {
+        ServerSettings {
+            webhook_secret: Some("s3cret".to_string()),
+            webhook_attempts: 3,
+            webhook_retry_interval: 0.2,
+            allow_private_callbacks: allow_private,
+            ..ServerSettings::default()
+        }
+    }
+
+    fn finished_job(id: &str) -> Job {
+        Job {
+            id: id.to_string(),
+            tenant: None,
+            status: JobStatus::Succeeded,
+            progress: StageProgress::new(Stage::Finished),
+            created_at: 1,
+            started_at: Some(2),
+            finished_at: Some(3),
+            error: None,
+            attempts: 1,
+            deliveries: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn signature_covers_timestamp_and_body() {
+        let (url, received) = receiver(vec![200]).await;
+        let webhook = Webhook::from_settings(&settings(true)).unwrap();
+
+        let delivery = webhook
+            .deliver(&url, "job.succeeded", &finished_job("j1"), 1)
+            .await;
+        assert!(delivery.succeeded(), "{:?}", delivery.error);
+        assert_eq!(delivery.status, Some(200));
+
+        let request = received.lock().unwrap()[0].clone();
+        let timestamp: u64 = request.headers["x-autovideo-timestamp"].parse().unwrap();
+        assert_eq!(timestamp, delivery.at);
+        let expected = format!(
+            "sha256={}",
+            sign("s3cret", timestamp, &request.body).unwrap()
+        );
+        assert_eq!(request.headers["x-autovideo-signature"], expected);
+        assert_eq!(request.headers["x-autovideo-event"], "job.succeeded");
+
+        let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(payload["event"], "job.succeeded");
+        assert_eq!(payload["job"]["id"], "j1");
+    }
+
+    #[tokio::test]
+    async fn private_addresses_are_refused_by_default() {
+        let (url, received) = receiver(vec![200]).await;
+        let webhook = Webhook::from_settings(&settings(false)).unwrap();
+
+        assert!(webhook.validate_url(&url).is_err());
+        assert!(webhook.validate_url("http://10.1.2.3/hook").is_err());
+        assert!(webhook.validate_url("http://[::1]:8080/hook").is_err());
+        assert!(webhook.validate_url("ftp://example.com/hook").is_err());
+        assert!(webhook.validate_url("https://example.com/hook").is_ok());
+
+        // 域名解析到回环地址时在投递前拒绝，不发起连接
+        let url = url.replace("127.0.0.1", "localhost");
+        let delivery = webhook
+            .deliver(&url, "job.failed", &finished_job("j2"), 1)
+            .await;
+        assert!(!delivery.succeeded());
+        assert!(delivery.error.unwrap().contains("non-public"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn public_address_classification() {
+        for ip in ["8.8.8.8", "2606:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.5.4",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:192.168.1.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn retry_delay_doubles() {
+        let webhook = Webhook::from_settings(&settings(true)).unwrap();
+        assert_eq!(webhook.delay(1), Duration::ZERO);
+        assert_eq!(webhook.delay(2), Duration::from_secs_f64(0.2));
+        assert_eq!(webhook.delay(3), Duration::from_secs_f64(0.4));
+        assert_eq!(webhook.delay(4), Duration::from_secs_f64(0.8));
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_and_recorded_in_job_file() {
+        let (url, received) = receiver(vec![503, 500, 200]).await;
+        let jobs_dir = std::env::temp_dir().join(format!(
+            "auto-video-webhook-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        let jobs_dir_str = jobs_dir.to_string_lossy().into_owned();
+
+        // 已结束但回调尚未送达的任务，服务启动时继续投递
+        JobStore::new(jobs_dir_str.clone())
+            .save(&JobRecord {
+                job: finished_job("done"),
+                request: JobRequest {
+                    text: "text".to_string(),
+                    options: toml::Table::new(),
+                    callback_url: Some(url),
+                },
+                work_dir: jobs_dir.join("done").to_string_lossy().into_owned(),
+                output: jobs_dir
+                    .join("done/output.mp4")
+                    .to_string_lossy()
+                    .into_owned(),
+            })
+            .unwrap();
+
+        let server = toml::Value::try_from(ServerSettings {
+            jobs_dir: jobs_dir_str,
+            ..settings(true)
+        })
+        .unwrap();
+        let mut file = toml::Table::new();
+        file.insert("server".to_string(), server);
+        let config = LoadedConfig::assemble(
+            vec![(PathBuf::from("test.toml"), file)],
+            Vec::new(),
+            CliOverrides::default(),
+        )
+        .unwrap();
+        let (queue, _workers) = JobQueue::start(config).await.unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while queue.get("done").unwrap().deliveries.len() < 3 {
+            assert!(Instant::now() < deadline, "deliveries did not finish");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        queue.shutdown();
+
+        let record: JobRecord = serde_json::from_str(
+            &std::fs::read_to_string(jobs_dir.join("done").join(JOB_FILE)).unwrap(),
+        )
+        .unwrap();
+        let statuses: Vec<_> = record.job.deliveries.iter().map(|d| d.status).collect();
+        assert_eq!(statuses, [Some(503), Some(500), Some(200)]);
+        let attempts: Vec<_> = record.job.deliveries.iter().map(|d| d.attempt).collect();
+        assert_eq!(attempts, [1, 2, 3]);
+        assert!(record.job.deliveries[2].succeeded());
+
+        // 重试间隔按 0.2s、0.4s 倍增
+        let times: Vec<_> = received.lock().unwrap().iter().map(|r| r.at).collect();
+        assert_eq!(times.len(), 3);
+        assert!(times[1] - times[0] >= Duration::from_millis(200));
+        assert!(times[2] - times[1] >= Duration::from_millis(400));
+
+        std::fs::remove_dir_all(&jobs_dir).ok();
+    }
+}
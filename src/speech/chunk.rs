@@ -0,0 +1,281 @@
+/// 句末标点：在这些字符之后切分优先级最高
+pub const SENTENCE_BREAKS: &[char] = &['。', '！', '？', '!', '?', '.', '…', '\n'];
+
+/// 分句标点：单句超长时在这些字符之后切分
+pub const CLAUSE_BREAKS: &[char] = &['，', '、', '；', '：', ',', ';', ':'];
+
+/// 句末标点后可能紧跟的右引号/括号，应与前一句保持在一起
+const CLOSING_MARKS: &[char] = &['”', '’', '」', '』', '）', ')', '"', '\''];
+
+/// 以英文句号结尾但不表示句末的常见缩写（不区分大小写）
+const ABBREVIATIONS: &[&str] = &[
+    "e.g.", "i.e.", "etc.", "vs.", "mr.", "mrs.", "ms.", "dr.", "prof.", "st.",
+];
+
+/// TTS 单次请求的文本长度限制
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkLimits {
+    /// 按 DashScope 规则计数（汉字等宽字符计 2，其余计 1）的上限
+    pub max_units: usize,
+    /// UTF-8 字节数上限
+    pub max_bytes: usize,
+}
+
+impl ChunkLimits {
+    /// qwen3-tts-flash：汉字按 2 个字符计算，最多 600 字符；保守起见取 500
+    pub const QWEN_TTS: ChunkLimits = ChunkLimits {
+        max_units: 500,
+        max_bytes: 1500,
+    };
+
+    fn fits(&self, text: &str) -> bool {
+        tts_units(text) <= self.max_units && text.len() <= self.max_bytes
+    }
+}
+
+/// 按 DashScope 的规则计算文本长度：CJK 及全角字符计 2，其余计 1
+pub fn tts_units(text: &str) -> usize {
+    text.chars().map(|c| if is_wide(c) { 2 } else { 1 }).sum()
+}
+
+pub fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303F
+        | 0x3040..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA960..=0xA97F
+        | 0xAC00..=0xD7AF
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFF)
+}
+
+/// 将文本切分为不超过限制的 TTS 分段
+///
+/// 优先在句末标点处切分，单句超长时退到分句标点，
+/// 仍然超长的片段才按长度硬切。
+pub fn split_for_tts(text: &str, limits: &ChunkLimits) -> Vec<String> {
+    let mut pieces = Vec::new();
+
+    for sentence in split_after(text, SENTENCE_BREAKS) {
+        if limits.fits(sentence) {
+            pieces.push(sentence.to_string());
+            continue;
+        }
+
+        for clause in split_after(sentence, CLAUSE_BREAKS) {
+            if limits.fits(clause) {
+                pieces.push(clause.to_string());
+            } else {
+                pieces.extend(hard_split(clause, limits));
+            }
+        }
+    }
+
+    pack(pieces, limits)
+}
+
+/// 分段是否在句末结束（允许句末标点后跟右引号/括号），分句处或硬切处返回 false
+pub fn ends_sentence(chunk: &str) -> bool {
+    chunk
+        .trim_end()
+        .trim_end_matches(CLOSING_MARKS)
+        .chars()
+        .next_back()
+        .is_some_and(|c| SENTENCE_BREAKS.contains(&c))
+}
+
+/// 在指定标点之后切分，标点（及紧随的右引号）保留在前一段末尾
+fn split_after<'a>(text: &'a str, breaks: &[char]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !breaks.contains(&c) {
+            continue;
+        }
+
+        // 英文句号只在其后为空白或文本末尾时视为句末，避免切开 3.14；e.g.、Mr. 等缩写也不切分
+        if c == '.'
+            && (chars.peek().is_some_and(|(_, next)| !next.is_whitespace())
+                || is_abbreviation(&text[..=i]))
+        {
+            continue;
+        }
+
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if CLOSING_MARKS.contains(&next) || breaks.contains(&next) {
+                end = j + next.len_utf8();
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        parts.push(&text[start..end]);
+        start = end;
+    }
+
+    if start < text.len() {
+        parts.push(&text[start..]);
+    }
+    parts
+}
+
+/// `text` 的最后一个词是否为常见缩写
+fn is_abbreviation(text: &str) -> bool {
+    let word = text
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(|c: char| !c.is_ascii_alphanumeric());
+    ABBREVIATIONS
+        .iter()
+        .any(|abbreviation| abbreviation.eq_ignore_ascii_case(word))
+}
+
+/// 按长度硬切，英文尽量在空白处断开
+fn hard_split(text: &str, limits: &ChunkLimits) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut last_space = None;
+
+    for c in text.chars() {
+        current.push(c);
+        if c.is_whitespace() {
+            last_space = Some(current.len());
+        }
+
+        if !limits.fits(&current) {
+            let overflow = current.pop().map(String::from).unwrap_or_default();
+            // 空白位置太靠前时直接硬切，避免产生过短的分段
+            match last_space.filter(|&pos| pos > current.len() / 2 && pos <= current.len()) {
+                Some(pos) => {
+                    let tail = current.split_off(pos);
+                    parts.push(std::mem::take(&mut current));
+                    current = tail;
+                }
+                None => parts.push(std::mem::take(&mut current)),
+            }
+            current.push_str(&overflow);
+            last_space = None;
+        }
+    }
+
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// 将相邻片段贪心合并为尽量长的分段
+fn pack(pieces: Vec<String>, limits: &ChunkLimits) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for piece in pieces {
+        let mut candidate = current.clone();
+        candidate.push_str(&piece);
+        if limits.fits(&candidate) {
+            current = candidate;
+        } else {
+            if !current.trim().is_empty() {
+                chunks.push(current.trim().to_string());
+            }
+            current = piece;
+        }
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: ChunkLimits = ChunkLimits {
+        max_units: 20,
+        max_bytes: 1500,
+    };
+
+    #[test]
+    fn ends_sentence_only_at_sentence_final_punctuation() {
+        assert!(ends_sentence("今天天气很好。"));
+        assert!(ends_sentence("He said \"stop!\" "));
+        assert!(ends_sentence("真的吗？”"));
+        assert!(!ends_sentence("虽然下着雨，"));
+        assert!(!ends_sentence("an unfinished clause;"));
+        assert!(!ends_sentence("硬切的片段"));
+        assert!(!ends_sentence(""));
+    }
+
+    #[test]
+    fn tts_units_count_wide_characters_twice() {
+        assert_eq!(tts_units("abc"), 3);
+        assert_eq!(tts_units("春天"), 4);
+        assert_eq!(tts_units("春a，"), 5);
+    }
+
+    #[test]
+    fn split_for_tts_packs_whole_sentences() {
+        let chunks = split_for_tts("春天来了。花开了。小鸟在唱歌。", &SMALL);
+        assert_eq!(chunks, vec!["春天来了。花开了。", "小鸟在唱歌。"]);
+        assert!(chunks.iter().all(|c| tts_units(c) <= SMALL.max_units));
+    }
+
+    #[test]
+    fn split_for_tts_falls_back_to_clauses_then_hard_split() {
+        let chunks = split_for_tts("春天来了，花开了，小鸟在树上唱歌，", &SMALL);
+        assert_eq!(chunks, vec!["春天来了，花开了，", "小鸟在树上唱歌，"]);
+
+        let long = "春".repeat(25);
+        let chunks = split_for_tts(&long, &SMALL);
+        assert_eq!(
+            chunks,
+            vec!["春".repeat(10), "春".repeat(10), "春".repeat(5)]
+        );
+    }
+
+    #[test]
+    fn split_for_tts_keeps_closing_quotes_and_decimals() {
+        let chunks = split_for_tts(
+            "他说：“走吧。”我们出发了。",
+            &ChunkLimits {
+                max_units: 16,
+                ..SMALL
+            },
+        );
+        assert_eq!(chunks, vec!["他说：“走吧。”", "我们出发了。"]);
+
+        let parts = split_after("Pi is 3.14. Next.", SENTENCE_BREAKS);
+        assert_eq!(parts, vec!["Pi is 3.14.", " Next."]);
+
+        let parts = split_after(
+            "Fruit, e.g. apples. Ask Dr. Lee (i.e. me). Done.",
+            SENTENCE_BREAKS,
+        );
+        assert_eq!(
+            parts,
+            vec!["Fruit, e.g. apples.", " Ask Dr. Lee (i.e. me).", " Done."]
+        );
+    }
+
+    #[test]
+    fn split_for_tts_respects_byte_limit() {
+        let limits = ChunkLimits {
+            max_units: 500,
+            max_bytes: 15,
+        };
+        let chunks = split_for_tts("春天来了。花开了。", &limits);
+        assert_eq!(chunks, vec!["春天来了。", "花开了。"]);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::error::{Result, VideoError};
+use crate::speech::{normalize_text, SpokenLanguage};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+/// 工作目录中自动加载的发音词典文件名
+pub const LEXICON_FILES: &[&str] = &["lexicon.toml", "lexicon.json"];
+
+/// 发音词典：书面写法 → 朗读写法，只作用于送入 TTS 的文本
+///
+/// TOML 或 JSON 的一层键值表，例如 `"GPT" = "G P T"`、`"重庆" = "崇庆"`。
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    /// 按书面写法长度降序排列，保证最长匹配优先
+    entries: Vec<(String, String)>,
+}
+
+impl Lexicon {
+    /// 按扩展名解析 TOML 或 JSON 词典
+    pub async fn load(path: &str) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let is_toml = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+
+        let map: BTreeMap<String, String> = if is_toml {
+            toml::from_str(&content)
+                .map_err(|e| VideoError::Config(format!("Invalid lexicon {}: {}", path, e)))?
+        } else {
+            serde_json::from_str(&content)
+                .map_err(|e| VideoError::Config(format!("Invalid lexicon {}: {}", path, e)))?
+        };
+
+        let lexicon = Self::from_entries(map);
+        info!("Loaded {} lexicon entries from {}", lexicon.len(), path);
+        Ok(lexicon)
+    }
+
+    /// 加载指定词典；未指定时查找工作目录下的默认词典，都不存在时返回空词典
+    pub async fn discover(path: Option<&str>, work_dir: &str) -> Result<Self> {
+        if let Some(path) = path {
+            return Self::load(path).await;
+        }
+        for name in LEXICON_FILES {
+            let candidate = format!("{}/{}", work_dir, name);
+            if Path::new(&candidate).is_file() {
+                return Self::load(&candidate).await;
+            }
+        }
+        Ok(Self::default())
+    }
+
+    pub fn from_entries(map: BTreeMap<String, String>) -> Self {
+        let mut entries: Vec<(String, String)> = map
+            .into_iter()
+            .filter(|(written, _)| !written.is_empty())
+            .collect();
+        entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 自左向右替换，替换结果不再参与匹配
+    ///
+    /// 以英文字母或数字开头/结尾的词条要求单词边界，避免 "AI" 命中 "SAID"。
+    pub fn apply(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut pos = 0;
+        while pos < text.len() {
+            let rest = &text[pos..];
+            let matched = self.entries.iter().find(|(written, _)| {
+                rest.starts_with(written.as_str())
+                    && word_boundary(text[..pos].chars().next_back(), written.chars().next())
+                    && word_boundary(
+                        rest[written.len()..].chars().next(),
+                        written.chars().next_back(),
+                    )
+            });
+
+            match matched {
+                Some((written, spoken)) => {
+                    out.push_str(spoken);
+                    pos += written.len();
+                }
+                None => {
+                    let c = rest.chars().next().unwrap_or_default();
+                    out.push(c);
+                    pos += c.len_utf8();
+                }
+            }
+        }
+        out
+    }
+}
+
+/// 送入 TTS 前的文本改写：先查发音词典，再做数字、日期、金额等规范化
+#[derive(Debug, Clone, Default)]
+pub struct Pronunciation {
+    pub lexicon: Lexicon,
+    /// 是否启用内置的中英文规范化
+    pub normalize: bool,
+}
+
+impl Pronunciation {
+    /// 得到朗读文本；字幕仍使用原始书面文本
+    pub fn spoken(&self, text: &str, language: &str) -> String {
+        let spoken = self.lexicon.apply(text);
+        if !self.normalize {
+            return spoken;
+        }
+        match SpokenLanguage::for_text(language, &spoken) {
+            Some(language) => normalize_text(&spoken, language),
+            None => spoken,
+        }
+    }
+
+    /// 逐词改写的朗读文本，供逐字时间估算按朗读长度分配权重
+    ///
+    /// 规范化语言按整句判断，避免 Auto 时单个数字被当作英文。
+    pub fn word_expander<'a>(&'a self, text: &str, language: &str) -> impl Fn(&str) -> String + 'a {
+        let language = SpokenLanguage::for_text(language, &self.lexicon.apply(text))
+            .filter(|_| self.normalize);
+        move |word| {
+            let spoken = self.lexicon.apply(word);
+            match language {
+                Some(language) => normalize_text(&spoken, language),
+                None => spoken,
+            }
+        }
+    }
+}
+
+/// 词条边缘是英文字母或数字时，相邻字符不能也是英文字母或数字
+fn word_boundary(neighbour: Option<char>, edge: Option<char>) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric();
+    !(edge.is_some_and(is_word) && neighbour.is_some_and(is_word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexicon(entries: &[(&str, &str)]) -> Lexicon {
+        Lexicon::from_entries(
+            entries
+                .iter()
+                .map(|(written, spoken)| (written.to_string(), spoken.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn apply_prefers_the_longest_entry() {
+        let lexicon = lexicon(&[("重庆", "崇庆"), ("重庆市", "崇庆市"), ("GPT", "G P T")]);
+        assert_eq!(lexicon.apply("重庆市和重庆"), "崇庆市和崇庆");
+        assert_eq!(lexicon.apply("GPT-4"), "G P T-4");
+    }
+
+    #[test]
+    fn apply_requires_word_boundaries_for_latin_entries() {
+        let lexicon = lexicon(&[("AI", "A I"), ("行", "航")]);
+        assert_eq!(lexicon.apply("AI SAID AIR"), "A I SAID AIR");
+        assert_eq!(lexicon.apply("用AI写作"), "用A I写作");
+        assert_eq!(lexicon.apply("AI2"), "AI2");
+        // 汉字词条不受单词边界限制
+        assert_eq!(lexicon.apply("银行行长"), "银航航长");
+    }
+
+    #[test]
+    fn apply_does_not_rescan_replacements() {
+        let lexicon = lexicon(&[("A", "AB"), ("B", "C")]);
+        assert_eq!(lexicon.apply("A B"), "AB C");
+    }
+
+    #[test]
+    fn spoken_applies_lexicon_before_normalisation() {
+        let pronunciation = Pronunciation {
+            lexicon: lexicon(&[("V2", "V two")]),
+            normalize: true,
+        };
+        assert_eq!(
+            pronunciation.spoken("V2 costs $3", "English"),
+            "V two costs three dollars"
+        );
+        assert_eq!(pronunciation.spoken("第2名", "Chinese"), "第二名");
+    }
+}
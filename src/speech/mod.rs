@@ -0,0 +1,13 @@
+pub mod chunk;
+pub mod lexicon;
+pub mod normalize;
+pub mod pacing;
+pub mod timing;
+pub mod voice;
+
+pub use chunk::{ends_sentence, split_for_tts, ChunkLimits};
+pub use lexicon::{Lexicon, Pronunciation};
+pub use normalize::{normalize_text, SpokenLanguage};
+pub use pacing::Pacing;
+pub use timing::{estimate_word_timings, WordTiming};
+pub use voice::{Voice, VoiceCatalogue, VoiceSettings};
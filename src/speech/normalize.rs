@@ -0,0 +1,453 @@
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// 文本规范化使用的朗读语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpokenLanguage {
+    Chinese,
+    English,
+}
+
+impl SpokenLanguage {
+    /// 根据 TTS 语言选择规范化规则；Auto 时按文本是否含汉字判断，其他语言不处理
+    pub fn for_text(language: &str, text: &str) -> Option<Self> {
+        match language.to_ascii_lowercase().as_str() {
+            "chinese" => Some(Self::Chinese),
+            "english" => Some(Self::English),
+            "auto" if text.chars().any(|c| ('\u{4E00}'..='\u{9FFF}').contains(&c)) => {
+                Some(Self::Chinese)
+            }
+            "auto" => Some(Self::English),
+            _ => None,
+        }
+    }
+}
+
+/// 千分位：1,234,567
+static GROUPED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d{1,3}(?:,\d{3})+(?:\.\d+)?").unwrap());
+/// 日期：2024-03-05、2024/3/5、2024.3.5、2024年3月5日
+static DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d{4})(?:[-/.](\d{1,2})[-/.](\d{1,2})\b|年(\d{1,2})月(\d{1,2})[日号]?)").unwrap()
+});
+/// 单独的年份：2024年
+static YEAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d{4})年").unwrap());
+/// 货币符号在前：¥100、$9.99、€5、£20
+static CURRENCY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([¥￥$€£])\s?(\d+(?:\.\d+)?)").unwrap());
+/// 百分数：50%、12.5％
+static PERCENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d+(?:\.\d+)?)\s?[%％]").unwrap());
+/// 其余数字
+static NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+(?:\.\d+)?").unwrap());
+
+/// 将数字、百分数、日期和金额改写为朗读形式
+pub fn normalize_text(text: &str, language: SpokenLanguage) -> String {
+    let text = GROUPED.replace_all(text, |caps: &Captures| caps[0].replace(',', ""));
+
+    match language {
+        SpokenLanguage::Chinese => normalize_chinese(&text),
+        SpokenLanguage::English => normalize_english(&text),
+    }
+}
+
+fn normalize_chinese(text: &str) -> String {
+    let text = DATE.replace_all(text, |caps: &Captures| {
+        let (month, day) = match (caps.get(2), caps.get(4)) {
+            (Some(month), _) => (month.as_str(), &caps[3]),
+            (None, Some(month)) => (month.as_str(), &caps[5]),
+            _ => return caps[0].to_string(),
+        };
+        format!(
+            "{}年{}月{}日",
+            zh_digits(&caps[1]),
+            zh_integer(month.trim_start_matches('0')),
+            zh_integer(day.trim_start_matches('0'))
+        )
+    });
+    let text = YEAR.replace_all(&text, |caps: &Captures| {
+        format!("{}年", zh_digits(&caps[1]))
+    });
+    let text = CURRENCY.replace_all(&text, |caps: &Captures| {
+        let unit = match &caps[1] {
+            "$" => "美元",
+            "€" => "欧元",
+            "£" => "英镑",
+            _ => "元",
+        };
+        format!("{}{}", zh_number(&caps[2]), unit)
+    });
+    let text = PERCENT.replace_all(&text, |caps: &Captures| {
+        format!("百分之{}", zh_number(&caps[1]))
+    });
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in NUMBER.find_iter(&text) {
+        out.push_str(&text[last..m.start()]);
+        let next = text[m.end()..].chars().next();
+        // 量词前的 2 读作“两”，序数（第2名）仍读“二”
+        if m.as_str() == "2"
+            && !text[..m.start()].ends_with('第')
+            && next.is_some_and(|c| "个位只条次种台本张头件名辆家岁天".contains(c))
+        {
+            out.push('两');
+        } else {
+            out.push_str(&zh_number(m.as_str()));
+        }
+        last = m.end();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+const ZH_DIGITS: [char; 10] = ['零', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+/// 逐位朗读：2024 → 二零二四
+fn zh_digits(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10).map(|d| ZH_DIGITS[d as usize]))
+        .collect()
+}
+
+/// 小数部分逐位朗读：3.14 → 三点一四
+fn zh_number(number: &str) -> String {
+    match number.split_once('.') {
+        Some((int, frac)) => format!("{}点{}", zh_integer(int), zh_digits(frac)),
+        None => zh_integer(number),
+    }
+}
+
+/// 整数按万/亿分节朗读；以 0 开头或过长的数字串（编号、电话）逐位朗读
+fn zh_integer(digits: &str) -> String {
+    if (digits.len() > 1 && digits.starts_with('0')) || digits.len() > 16 {
+        return zh_digits(digits);
+    }
+    let value: u64 = digits.parse().unwrap_or(0);
+    if value == 0 {
+        return "零".to_string();
+    }
+
+    const SECTION_UNITS: [&str; 4] = ["", "万", "亿", "万亿"];
+    let mut sections = Vec::new();
+    let mut rest = value;
+    while rest > 0 {
+        sections.push(rest % 10000);
+        rest /= 10000;
+    }
+
+    let mut out = String::new();
+    let mut pending_zero = false;
+    for (i, &section) in sections.iter().enumerate().rev() {
+        if section == 0 {
+            pending_zero = !out.is_empty();
+            continue;
+        }
+        if !out.is_empty() && (pending_zero || section < 1000) {
+            out.push('零');
+        }
+        pending_zero = false;
+        out.push_str(&zh_section(section));
+        out.push_str(SECTION_UNITS[i]);
+    }
+
+    // 十几读作“十几”而非“一十几”
+    match out.strip_prefix("一十") {
+        Some(rest) => format!("十{}", rest),
+        None => out,
+    }
+}
+
+/// 读一个不超过 9999 的分节
+fn zh_section(section: u64) -> String {
+    const UNITS: [&str; 4] = ["千", "百", "十", ""];
+    let digits = [
+        section / 1000,
+        section / 100 % 10,
+        section / 10 % 10,
+        section % 10,
+    ];
+
+    let mut out = String::new();
+    let mut zero = false;
+    for (digit, unit) in digits.iter().zip(UNITS) {
+        if *digit == 0 {
+            zero = !out.is_empty();
+            continue;
+        }
+        if zero {
+            out.push('零');
+            zero = false;
+        }
+        out.push(ZH_DIGITS[*digit as usize]);
+        out.push_str(unit);
+    }
+    out
+}
+
+const EN_ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const EN_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn normalize_english(text: &str) -> String {
+    let text = DATE.replace_all(text, |caps: &Captures| {
+        let (month, day) = match (caps.get(2), caps.get(4)) {
+            (Some(month), _) => (month.as_str(), &caps[3]),
+            (None, Some(month)) => (month.as_str(), &caps[5]),
+            _ => return caps[0].to_string(),
+        };
+        let month: usize = month.parse().unwrap_or(0);
+        let day: u64 = day.parse().unwrap_or(0);
+        match EN_MONTHS.get(month.wrapping_sub(1)) {
+            Some(name) if (1..=31).contains(&day) => format!(
+                "{} {}, {}",
+                name,
+                en_ordinal(day),
+                en_year(caps[1].parse().unwrap_or(0))
+            ),
+            _ => caps[0].to_string(),
+        }
+    });
+    let text = CURRENCY.replace_all(&text, |caps: &Captures| {
+        let (major, minor) = match &caps[1] {
+            "$" => ("dollar", Some("cent")),
+            "€" => ("euro", Some("cent")),
+            "£" => ("pound", Some("penny")),
+            _ => ("yuan", None),
+        };
+        en_money(&caps[2], major, minor)
+    });
+    let text = PERCENT.replace_all(&text, |caps: &Captures| {
+        format!("{} percent", en_number(&caps[1]))
+    });
+    NUMBER
+        .replace_all(&text, |caps: &Captures| en_number(&caps[0]))
+        .into_owned()
+}
+
+/// 金额：$9.99 → nine dollars and ninety-nine cents
+fn en_money(amount: &str, major: &str, minor: Option<&str>) -> String {
+    let (int, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    let whole: u64 = int.parse().unwrap_or(0);
+    let plural = |n: u64, unit: &str| match (n, unit) {
+        (1, _) | (_, "yuan") => unit.to_string(),
+        (_, "penny") => "pence".to_string(),
+        _ => format!("{}s", unit),
+    };
+
+    let mut out = format!("{} {}", en_integer(int), plural(whole, major));
+    if let (Some(minor), 2) = (minor, frac.len()) {
+        let cents: u64 = frac.parse().unwrap_or(0);
+        if cents > 0 {
+            out.push_str(&format!(
+                " and {} {}",
+                en_cardinal(cents),
+                plural(cents, minor)
+            ));
+        }
+    } else if !frac.is_empty() {
+        out = format!("{} {}", en_number(amount), plural(2, major));
+    }
+    out
+}
+
+fn en_number(number: &str) -> String {
+    match number.split_once('.') {
+        Some((int, frac)) => {
+            let digits: Vec<&str> = frac
+                .chars()
+                .filter_map(|c| c.to_digit(10).map(|d| EN_ONES[d as usize]))
+                .collect();
+            format!("{} point {}", en_integer(int), digits.join(" "))
+        }
+        None => en_integer(number),
+    }
+}
+
+fn en_integer(digits: &str) -> String {
+    if (digits.len() > 1 && digits.starts_with('0')) || digits.len() > 15 {
+        let words: Vec<&str> = digits
+            .chars()
+            .filter_map(|c| c.to_digit(10).map(|d| EN_ONES[d as usize]))
+            .collect();
+        return words.join(" ");
+    }
+    en_cardinal(digits.parse().unwrap_or(0))
+}
+
+fn en_cardinal(value: u64) -> String {
+    const SCALES: [(u64, &str); 4] = [
+        (1_000_000_000_000, "trillion"),
+        (1_000_000_000, "billion"),
+        (1_000_000, "million"),
+        (1_000, "thousand"),
+    ];
+
+    if value < 20 {
+        return EN_ONES[value as usize].to_string();
+    }
+    if value < 100 {
+        let tens = EN_TENS[(value / 10) as usize];
+        return match value % 10 {
+            0 => tens.to_string(),
+            ones => format!("{}-{}", tens, EN_ONES[ones as usize]),
+        };
+    }
+    if value < 1000 {
+        let hundreds = format!("{} hundred", EN_ONES[(value / 100) as usize]);
+        return match value % 100 {
+            0 => hundreds,
+            rest => format!("{} {}", hundreds, en_cardinal(rest)),
+        };
+    }
+
+    let mut parts = Vec::new();
+    let mut rest = value;
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            parts.push(format!("{} {}", en_cardinal(rest / scale), name));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        parts.push(en_cardinal(rest));
+    }
+    parts.join(" ")
+}
+
+/// 年份按两位一组朗读：1999 → nineteen ninety-nine，2005 → two thousand five
+fn en_year(year: u64) -> String {
+    match (year / 100, year % 100) {
+        (_, _) if !(1100..=2099).contains(&year) => en_cardinal(year),
+        (20, rest) if rest < 10 => en_cardinal(year),
+        (century, 0) => format!("{} hundred", en_cardinal(century)),
+        (century, rest) if rest < 10 => {
+            format!("{} oh {}", en_cardinal(century), EN_ONES[rest as usize])
+        }
+        (century, rest) => format!("{} {}", en_cardinal(century), en_cardinal(rest)),
+    }
+}
+
+fn en_ordinal(value: u64) -> String {
+    let cardinal = en_cardinal(value);
+    let irregular = [
+        ("one", "first"),
+        ("two", "second"),
+        ("three", "third"),
+        ("five", "fifth"),
+        ("eight", "eighth"),
+        ("nine", "ninth"),
+        ("twelve", "twelfth"),
+    ];
+    for (word, ordinal) in irregular {
+        if let Some(stem) = cardinal.strip_suffix(word) {
+            return format!("{}{}", stem, ordinal);
+        }
+    }
+    match cardinal.strip_suffix('y') {
+        Some(stem) => format!("{}ieth", stem),
+        None => format!("{}th", cardinal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zh(text: &str) -> String {
+        normalize_text(text, SpokenLanguage::Chinese)
+    }
+
+    fn en(text: &str) -> String {
+        normalize_text(text, SpokenLanguage::English)
+    }
+
+    #[test]
+    fn language_follows_tts_setting() {
+        assert_eq!(
+            SpokenLanguage::for_text("Chinese", "abc"),
+            Some(SpokenLanguage::Chinese)
+        );
+        assert_eq!(
+            SpokenLanguage::for_text("auto", "共 3 个"),
+            Some(SpokenLanguage::Chinese)
+        );
+        assert_eq!(
+            SpokenLanguage::for_text("Auto", "3 apples"),
+            Some(SpokenLanguage::English)
+        );
+        assert_eq!(SpokenLanguage::for_text("Japanese", "3"), None);
+    }
+
+    #[test]
+    fn chinese_numbers() {
+        assert_eq!(zh("10"), "十");
+        assert_eq!(zh("1,234,567"), "一百二十三万四千五百六十七");
+        assert_eq!(zh("100005"), "十万零五");
+        assert_eq!(zh("3.14"), "三点一四");
+        assert_eq!(zh("编号007"), "编号零零七");
+        assert_eq!(zh("2个苹果和2.5斤"), "两个苹果和二点五斤");
+        assert_eq!(zh("第2名和第2次"), "第二名和第二次");
+    }
+
+    #[test]
+    fn chinese_dates_money_and_percent() {
+        assert_eq!(zh("2024-03-05"), "二零二四年三月五日");
+        assert_eq!(zh("2024年3月5日"), "二零二四年三月五日");
+        assert_eq!(zh("2024年"), "二零二四年");
+        assert_eq!(zh("¥12.5"), "十二点五元");
+        assert_eq!(zh("$20"), "二十美元");
+        assert_eq!(zh("增长50%"), "增长百分之五十");
+    }
+
+    #[test]
+    fn english_numbers_dates_and_money() {
+        assert_eq!(en("21 apples"), "twenty-one apples");
+        assert_eq!(en("1,200"), "one thousand two hundred");
+        assert_eq!(en("3.14"), "three point one four");
+        assert_eq!(
+            en("1999-12-31"),
+            "December thirty-first, nineteen ninety-nine"
+        );
+        assert_eq!(en("2005/3/2"), "March second, two thousand five");
+        assert_eq!(en("$9.99"), "nine dollars and ninety-nine cents");
+        assert_eq!(en("£1"), "one pound");
+        assert_eq!(en("12.5%"), "twelve point five percent");
+    }
+}
@@ -0,0 +1,46 @@
+/// 旁白节奏：裁剪每段语音首尾的静音，再按层级插入固定停顿
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pacing {
+    /// 边缘静音裁剪阈值（dB），None 表示保留 TTS 原始静音
+    pub trim_threshold: Option<f64>,
+    /// 句与句之间的停顿（秒）
+    pub sentence_pause: f64,
+    /// 分镜之间的停顿（秒）
+    pub scene_pause: f64,
+    /// 视频开头的停顿（秒）
+    pub lead_in: f64,
+    /// 视频结尾的停顿（秒）
+    pub tail: f64,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self {
+            trim_threshold: Some(-50.0),
+            sentence_pause: 0.3,
+            scene_pause: 0.8,
+            lead_in: 0.0,
+            tail: 0.0,
+        }
+    }
+}
+
+impl Pacing {
+    /// 第 `position` 个分镜开头的停顿
+    pub fn before_scene(&self, position: usize) -> f64 {
+        if position == 0 {
+            self.lead_in.max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// 第 `position` 个分镜（共 `count` 个）结尾的停顿
+    pub fn after_scene(&self, position: usize, count: usize) -> f64 {
+        if position + 1 == count {
+            self.tail.max(0.0)
+        } else {
+            self.scene_pause.max(0.0)
+        }
+    }
+}
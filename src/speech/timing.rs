@@ -0,0 +1,187 @@
+use crate::speech::chunk::{is_wide, CLAUSE_BREAKS, SENTENCE_BREAKS};
+
+/// 句末标点后的停顿权重（相当于几个字）
+const SENTENCE_PAUSE_WEIGHT: f64 = 0.8;
+/// 分句标点后的停顿权重
+const CLAUSE_PAUSE_WEIGHT: f64 = 0.4;
+
+/// 一个字/词在该句语音中的时间段（秒，相对句首）
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordTiming {
+    /// 书面文本，包含紧随其后的标点与空白，依次拼接即为原句
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// 按字符权重估算逐字/逐词时间
+///
+/// qwen3-tts-flash 不返回时间戳，这里把整句时长按权重分配：
+/// 汉字等宽字符各计 1，英文单词约每 3 个字母计 1，数字每位计 1，
+/// 标点不发音但在其后留出停顿。支持时间戳的 TTS 可直接产出同样的结构。
+///
+/// 语音按朗读文本合成，`spoken` 给出每个书面词的朗读形式，
+/// 权重按朗读形式计算（如 `100%` 读作“百分之一百”），时间仍挂在书面词上。
+pub fn estimate_word_timings(
+    text: &str,
+    duration: f64,
+    spoken: impl Fn(&str) -> String,
+) -> Vec<WordTiming> {
+    let mut tokens = tokenize(text);
+    for token in tokens.iter_mut() {
+        let weight: f64 = tokenize(&spoken(&token.text))
+            .iter()
+            .map(|t| t.weight)
+            .sum();
+        if weight > 0.0 {
+            token.weight = weight;
+        }
+    }
+    // 句末静音已裁掉，最后一个词之后不留停顿
+    if let Some(last) = tokens.last_mut() {
+        last.pause = 0.0;
+    }
+    let total: f64 = tokens.iter().map(|t| t.weight + t.pause).sum();
+    if tokens.is_empty() || total <= 0.0 {
+        return Vec::new();
+    }
+
+    let scale = duration.max(0.0) / total;
+    let mut t = 0.0;
+    tokens
+        .into_iter()
+        .map(|token| {
+            let start = t;
+            let end = start + token.weight * scale;
+            t = end + token.pause * scale;
+            WordTiming {
+                text: token.text,
+                start,
+                end,
+            }
+        })
+        .collect()
+}
+
+struct Token {
+    text: String,
+    weight: f64,
+    pause: f64,
+}
+
+/// 汉字逐字、英文与数字按连续串切分，标点和空白并入前一个词
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    // 句首的标点/空白暂存，并入第一个词
+    let mut prefix = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() && !is_wide(c) {
+            let mut word = std::mem::take(&mut prefix);
+            word.push(c);
+            while let Some(&next) = chars.peek() {
+                if (next.is_alphanumeric() || next == '\'') && !is_wide(next) {
+                    word.push(next);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let digits = word.chars().filter(|c| c.is_ascii_digit()).count() as f64;
+            let letters = word.chars().filter(|c| c.is_alphabetic()).count() as f64;
+            tokens.push(Token {
+                text: word,
+                weight: (digits + letters / 3.0).max(1.0),
+                pause: 0.0,
+            });
+        } else if is_wide(c) && c.is_alphanumeric() {
+            let mut word = std::mem::take(&mut prefix);
+            word.push(c);
+            tokens.push(Token {
+                text: word,
+                weight: 1.0,
+                pause: 0.0,
+            });
+        } else {
+            let pause = if SENTENCE_BREAKS.contains(&c) {
+                SENTENCE_PAUSE_WEIGHT
+            } else if CLAUSE_BREAKS.contains(&c) {
+                CLAUSE_PAUSE_WEIGHT
+            } else {
+                0.0
+            };
+            match tokens.last_mut() {
+                Some(last) => {
+                    last.text.push(c);
+                    last.pause = last.pause.max(pause);
+                }
+                None => prefix.push(c),
+            }
+        }
+    }
+
+    // 只有标点的句子整体作为一个词
+    if tokens.is_empty() && !prefix.is_empty() {
+        tokens.push(Token {
+            text: prefix,
+            weight: 1.0,
+            pause: 0.0,
+        });
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::speech::{Lexicon, Pronunciation};
+
+    fn written(word: &str) -> String {
+        word.to_string()
+    }
+
+    #[test]
+    fn timings_cover_the_whole_line() {
+        let words = estimate_word_timings("你好，world!", 3.0, written);
+        let texts: Vec<_> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["你", "好，", "world!"]);
+        assert_eq!(words[0].start, 0.0);
+        assert!((words.last().unwrap().end - 3.0).abs() < 1e-9);
+        // 逗号之后留出停顿
+        assert!(words[2].start > words[1].end);
+    }
+
+    #[test]
+    fn punctuation_only_line_is_one_word() {
+        let words = estimate_word_timings("……", 1.0, written);
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].end, 1.0);
+    }
+
+    #[test]
+    fn weights_follow_the_spoken_expansion() {
+        let pronunciation = Pronunciation {
+            lexicon: Lexicon::default(),
+            normalize: true,
+        };
+        let text = "涨了100%";
+        let expand = pronunciation.word_expander(text, "Chinese");
+        assert_eq!(expand("100%"), "百分之一百");
+
+        let words = estimate_word_timings(text, 7.0, expand);
+        // “涨”“了”各计 1，“百分之一百”计 5
+        assert_eq!(words[2].text, "100%");
+        assert!((words[2].end - words[2].start - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn auto_language_is_decided_by_the_whole_line() {
+        let pronunciation = Pronunciation {
+            lexicon: Lexicon::default(),
+            normalize: true,
+        };
+        let expand = pronunciation.word_expander("共有12个", "Auto");
+        assert_eq!(expand("12"), "十二");
+    }
+}
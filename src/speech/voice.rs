@@ -0,0 +1,319 @@
+use crate::error::{Result, VideoError};
+use crate::speech::ChunkLimits;
+use serde::{Deserialize, Serialize};
+
+/// 旁白音色参数，未设置的字段沿用上一层配置
+///
+/// 生效顺序：音色目录默认值 → 项目（storyboard.json）→ 命令行 → 单个分镜
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoiceSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 语速倍率，1.0 为原速
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+    /// 音调倍率，1.0 为原调
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pitch: Option<f64>,
+    /// 音量 0-100，50 为原始音量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+}
+
+impl VoiceSettings {
+    /// 用 `other` 中已设置的字段覆盖当前配置
+    pub fn overlay(&self, other: &VoiceSettings) -> VoiceSettings {
+        VoiceSettings {
+            voice: other.voice.clone().or_else(|| self.voice.clone()),
+            language: other.language.clone().or_else(|| self.language.clone()),
+            rate: other.rate.or(self.rate),
+            pitch: other.pitch.or(self.pitch),
+            volume: other.volume.or(self.volume),
+            sample_rate: other.sample_rate.or(self.sample_rate),
+        }
+    }
+}
+
+/// 校验后的完整音色参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Voice {
+    pub voice: String,
+    pub language: String,
+    pub rate: f64,
+    pub pitch: f64,
+    pub volume: f64,
+    pub sample_rate: u32,
+    /// 模型实际输出的采样率
+    pub native_sample_rate: u32,
+}
+
+impl Voice {
+    /// 模型不支持原生调节的参数通过 FFmpeg 滤镜实现，无需处理时返回 None
+    pub fn post_filter(&self) -> Option<String> {
+        let mut filters = Vec::new();
+
+        if (self.pitch - 1.0).abs() > f64::EPSILON {
+            // 先改采样率变调，再用 atempo 还原时长
+            filters.push(format!(
+                "asetrate={:.0},aresample={},atempo={:.4}",
+                self.native_sample_rate as f64 * self.pitch,
+                self.native_sample_rate,
+                1.0 / self.pitch
+            ));
+        }
+        if (self.rate - 1.0).abs() > f64::EPSILON {
+            filters.push(format!("atempo={:.4}", self.rate));
+        }
+        if (self.volume - 50.0).abs() > f64::EPSILON {
+            filters.push(format!("volume={:.4}", self.volume / 50.0));
+        }
+        if self.sample_rate != self.native_sample_rate {
+            filters.push(format!("aresample={}", self.sample_rate));
+        }
+
+        if filters.is_empty() {
+            None
+        } else {
+            Some(filters.join(","))
+        }
+    }
+}
+
+/// 单个音色的说明
+#[derive(Debug)]
+pub struct VoiceInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// 某个 TTS 模型可用的音色与参数范围
+#[derive(Debug)]
+pub struct VoiceCatalogue {
+    pub model: &'static str,
+    pub voices: &'static [VoiceInfo],
+    pub languages: &'static [&'static str],
+    pub default_voice: &'static str,
+    pub default_language: &'static str,
+    pub native_sample_rate: u32,
+    /// 可输出的采样率（非原生采样率会在合成后重采样）
+    pub sample_rates: &'static [u32],
+    pub rate_range: (f64, f64),
+    pub pitch_range: (f64, f64),
+    pub volume_range: (f64, f64),
+    pub chunk_limits: ChunkLimits,
+}
+
+impl VoiceCatalogue {
+    /// qwen3-tts-flash 音色目录
+    pub const QWEN_TTS_FLASH: VoiceCatalogue = VoiceCatalogue {
+        model: "qwen3-tts-flash",
+        voices: &[
+            VoiceInfo {
+                name: "Cherry",
+                description: "芊悦，阳光积极的女声",
+            },
+            VoiceInfo {
+                name: "Ethan",
+                description: "晨煦，温暖有活力的男声",
+            },
+            VoiceInfo {
+                name: "Nofish",
+                description: "不吃鱼，不会翘舌音的设计师",
+            },
+            VoiceInfo {
+                name: "Jennifer",
+                description: "詹妮弗，品牌级美语女声",
+            },
+            VoiceInfo {
+                name: "Ryan",
+                description: "甜茶，节奏感强的男声",
+            },
+            VoiceInfo {
+                name: "Katerina",
+                description: "卡捷琳娜，御姐女声",
+            },
+            VoiceInfo {
+                name: "Elias",
+                description: "墨讲师，严谨的讲解男声",
+            },
+            VoiceInfo {
+                name: "Jada",
+                description: "上海-阿珍，沪上阿姐",
+            },
+            VoiceInfo {
+                name: "Dylan",
+                description: "北京-晓东，北京胡同少年",
+            },
+            VoiceInfo {
+                name: "Sunny",
+                description: "四川-晴儿，甜美川妹子",
+            },
+            VoiceInfo {
+                name: "Li",
+                description: "南京-老李，耐心的瑜伽老师",
+            },
+            VoiceInfo {
+                name: "Marcus",
+                description: "陕西-秦川，老陕男声",
+            },
+            VoiceInfo {
+                name: "Roy",
+                description: "闽南-阿杰，诙谐台湾哥仔",
+            },
+            VoiceInfo {
+                name: "Peter",
+                description: "天津-李彼得，天津相声捧哏",
+            },
+            VoiceInfo {
+                name: "Rocky",
+                description: "粤语-阿强，幽默风趣男声",
+            },
+            VoiceInfo {
+                name: "Kiki",
+                description: "粤语-阿清，甜美港妹闺蜜",
+            },
+            VoiceInfo {
+                name: "Eric",
+                description: "四川-程川，跳脱市井男声",
+            },
+        ],
+        languages: &[
+            "Auto",
+            "Chinese",
+            "English",
+            "Japanese",
+            "Korean",
+            "German",
+            "French",
+            "Russian",
+            "Portuguese",
+            "Spanish",
+            "Italian",
+        ],
+        default_voice: "Cherry",
+        default_language: "Chinese",
+        native_sample_rate: 24000,
+        sample_rates: &[16000, 22050, 24000, 44100, 48000],
+        rate_range: (0.5, 2.0),
+        pitch_range: (0.5, 2.0),
+        volume_range: (0.0, 100.0),
+        chunk_limits: ChunkLimits::QWEN_TTS,
+    };
+
+    /// 合并默认值并校验参数
+    pub fn resolve(&self, settings: &VoiceSettings) -> Result<Voice> {
+        let voice = settings.voice.as_deref().unwrap_or(self.default_voice);
+        let voice = self
+            .voices
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(voice))
+            .ok_or_else(|| {
+                VideoError::Scene(format!(
+                    "Unknown voice '{}' for {} (available: {})",
+                    voice,
+                    self.model,
+                    self.voices
+                        .iter()
+                        .map(|v| v.name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })?;
+
+        let language = settings
+            .language
+            .as_deref()
+            .unwrap_or(self.default_language);
+        let language = self
+            .languages
+            .iter()
+            .find(|l| l.eq_ignore_ascii_case(language))
+            .ok_or_else(|| {
+                VideoError::Scene(format!(
+                    "Unsupported language '{}' for {} (available: {})",
+                    language,
+                    self.model,
+                    self.languages.join(", ")
+                ))
+            })?;
+
+        let sample_rate = settings.sample_rate.unwrap_or(self.native_sample_rate);
+        if !self.sample_rates.contains(&sample_rate) {
+            return Err(VideoError::Scene(format!(
+                "Unsupported sample rate {} for {} (available: {:?})",
+                sample_rate, self.model, self.sample_rates
+            )));
+        }
+
+        Ok(Voice {
+            voice: voice.name.to_string(),
+            language: language.to_string(),
+            rate: check_range("rate", settings.rate.unwrap_or(1.0), self.rate_range)?,
+            pitch: check_range("pitch", settings.pitch.unwrap_or(1.0), self.pitch_range)?,
+            volume: check_range("volume", settings.volume.unwrap_or(50.0), self.volume_range)?,
+            sample_rate,
+            native_sample_rate: self.native_sample_rate,
+        })
+    }
+
+    /// 打印音色目录
+    pub fn print(&self) {
+        println!("Voices for {}:", self.model);
+        for voice in self.voices {
+            let marker = if voice.name == self.default_voice {
+                " (default)"
+            } else {
+                ""
+            };
+            println!("  {:<10} {}{}", voice.name, voice.description, marker);
+        }
+        println!("Languages: {}", self.languages.join(", "));
+        println!("Sample rates: {:?}", self.sample_rates);
+        println!(
+            "Rate: {}-{}, pitch: {}-{}, volume: {}-{}",
+            self.rate_range.0,
+            self.rate_range.1,
+            self.pitch_range.0,
+            self.pitch_range.1,
+            self.volume_range.0,
+            self.volume_range.1
+        );
+    }
+}
+
+/// 校验取值范围；NaN 与任何数比较都为假，需单独排除非有限值
+fn check_range(name: &str, value: f64, (min, max): (f64, f64)) -> Result<f64> {
+    if !value.is_finite() || value < min || value > max {
+        return Err(VideoError::Scene(format!(
+            "Voice {} {} out of range ({}-{})",
+            name, value, min, max
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_range_rejects_non_finite_values() {
+        assert!(check_range("rate", f64::NAN, (0.5, 2.0)).is_err());
+        assert!(check_range("rate", f64::INFINITY, (0.5, 2.0)).is_err());
+        assert!(check_range("rate", 2.5, (0.5, 2.0)).is_err());
+        assert_eq!(check_range("rate", 1.5, (0.5, 2.0)).unwrap(), 1.5);
+    }
+
+    #[test]
+    fn resolve_rejects_nan_pitch() {
+        let settings = VoiceSettings {
+            pitch: Some(f64::NAN),
+            ..Default::default()
+        };
+        assert!(VoiceCatalogue::QWEN_TTS_FLASH.resolve(&settings).is_err());
+    }
+}
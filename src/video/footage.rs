@@ -0,0 +1,66 @@
+use crate::media;
+use crate::scene::Scene;
+use crate::video::mix;
+use tracing::{info, warn};
+
+/// 为分镜的本地素材解析实际文件并识别图片/视频，找不到或无法解码的素材会被忽略，改用生成的图片
+pub async fn resolve_media(scenes: &mut [Scene], media_dir: Option<&str>) {
+    for scene in scenes.iter_mut() {
+        let Some(footage) = scene.media.as_mut() else {
+            continue;
+        };
+        let path = match (&footage.file, &footage.tag, media_dir) {
+            (Some(file), _, _) => Some(file.clone()),
+            (None, Some(tag), Some(dir)) => mix::find_tagged(dir, tag).await,
+            _ => None,
+        };
+        let Some(path) = path else {
+            warn!(
+                "Scene {}: no media file for {:?}, falling back to a generated image",
+                scene.index,
+                footage.tag.as_deref().unwrap_or("")
+            );
+            continue;
+        };
+
+        let info = match media::probe(&path).await {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("Scene {}: unusable media {}: {}", scene.index, path, e);
+                continue;
+            }
+        };
+        if let Err(e) = info.require_image(&path) {
+            warn!("Scene {}: {}", scene.index, e);
+            continue;
+        }
+
+        // 静态图片没有时长
+        footage.is_video = info.duration.is_some();
+        footage.has_audio = info.audio.is_some();
+        info!(
+            "Scene {} uses local {} {}",
+            scene.index,
+            if footage.is_video { "video" } else { "image" },
+            path
+        );
+        footage.resolved_path = Some(path);
+    }
+}
+
+/// 分镜使用的本地图片
+pub fn local_image(scene: &Scene) -> Option<String> {
+    scene
+        .media
+        .as_ref()
+        .filter(|footage| !footage.is_video)
+        .and_then(|footage| footage.resolved_path.clone())
+}
+
+/// 分镜是否已有本地素材，无需生成图片
+pub fn has_local_media(scene: &Scene) -> bool {
+    scene
+        .media
+        .as_ref()
+        .is_some_and(|footage| footage.resolved_path.is_some())
+}
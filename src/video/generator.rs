@@ -1,16 +1,141 @@
 use crate::error::{Result, VideoError};
-use crate::scene::Scene;
-use std::path::PathBuf;
-use std::process::Command;
+use crate::media::{self, LoudnessReport, LoudnessTarget};
+use crate::progress::{Event, EventBus};
+use crate::scene::{Line, Scene, SoundCue};
+use crate::video::mix::{self, MusicOptions};
+use crate::video::subtitle::{self, SubtitleOptions, SubtitleStyle};
+use crate::video::Transition;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
 use tracing::info;
 
 pub struct VideoGenerator {
     output_dir: String,
+    music: Option<MusicOptions>,
+    loudness: Option<LoudnessTarget>,
+    subtitles: SubtitleOptions,
+    fps: u32,
+    canvas: (u32, u32),
+    transition: Transition,
+    transition_duration: f64,
+    events: EventBus,
+}
+
+/// 分镜片段全部完成时的合成进度，其余部分留给拼接与混音
+const SEGMENTS_PERCENT: f64 = 80.0;
+
+/// Ken Burns 效果在一个分镜内的放大幅度
+const KEN_BURNS_ZOOM: f64 = 0.12;
+
+/// 分镜画面来源
+#[derive(Debug, Clone, Copy)]
+enum Visual<'a> {
+    /// 静态图片
+    Still(&'a str),
+    /// 缓慢推近的静态图片
+    KenBurns(&'a str),
+    /// 视频素材，从入点开始，不足分镜时长时循环或定格在最后一帧
+    Clip {
+        path: &'a str,
+        start: f64,
+        looped: bool,
+    },
+}
+
+impl Visual<'_> {
+    fn path(&self) -> &str {
+        match self {
+            Visual::Still(path) | Visual::KenBurns(path) | Visual::Clip { path, .. } => path,
+        }
+    }
+}
+
+/// 分镜的画面来源：视频素材优先，其次是图片，都没有时该分镜不生成片段
+fn visual_of(scene: &Scene) -> Option<Visual<'_>> {
+    let clip = scene.media.as_ref().and_then(|footage| {
+        footage.video().map(|path| Visual::Clip {
+            path,
+            start: footage.start.max(0.0),
+            looped: footage.looped,
+        })
+    });
+    let still = scene.image_path.as_deref().map(|path| {
+        if scene.ken_burns {
+            Visual::KenBurns(path)
+        } else {
+            Visual::Still(path)
+        }
+    });
+    clip.or(still)
+}
+
+/// 成片的合成结果
+#[derive(Debug, Clone)]
+pub struct RenderSummary {
+    pub duration: f64,
+    pub loudness: Option<LoudnessReport>,
 }
 
 impl VideoGenerator {
     pub fn new(output_dir: String) -> Self {
-        Self { output_dir }
+        Self {
+            output_dir,
+            music: None,
+            loudness: None,
+            subtitles: SubtitleOptions::default(),
+            fps: 30,
+            canvas: (1280, 720),
+            transition: Transition::Cut,
+            transition_duration: 0.0,
+            events: EventBus::default(),
+        }
+    }
+
+    /// 设置字幕样式、字体与高亮颜色
+    pub fn with_subtitles(mut self, subtitles: SubtitleOptions) -> Self {
+        self.subtitles = subtitles;
+        self
+    }
+
+    /// 设置输出帧率
+    pub fn with_fps(mut self, fps: u32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    /// 设置画面尺寸，素材按比例缩放并居中补边
+    pub fn with_canvas(mut self, width: u32, height: u32) -> Self {
+        self.canvas = (width, height);
+        self
+    }
+
+    /// 设置分镜之间的转场及其时长（秒）
+    pub fn with_transition(mut self, transition: Transition, duration: f64) -> Self {
+        self.transition = transition;
+        self.transition_duration = duration;
+        self
+    }
+
+    /// 添加背景音乐，旁白播放时自动压低音乐音量
+    pub fn with_music(mut self, music: Option<MusicOptions>) -> Self {
+        self.music = music;
+        self
+    }
+
+    /// 对最终混音做两遍 EBU R128 响度标准化
+    pub fn with_loudness(mut self, loudness: Option<LoudnessTarget>) -> Self {
+        self.loudness = loudness;
+        self
+    }
+
+    /// 把合成进度发送到事件总线
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    fn report(&self, percent: f64) {
+        self.events.emit(Event::RenderProgress { percent });
     }
 
     /// 合成最终视频
@@ -19,110 +144,243 @@ impl VideoGenerator {
         scenes: &[Scene],
         audio_path: &str,
         output_path: &str,
-    ) -> Result<()> {
+    ) -> Result<RenderSummary> {
         info!("Starting video generation...");
 
-        // 创建临时文件列表
-        let concat_file = format!("{}/concat.txt", self.output_dir);
-        let mut concat_content = String::new();
+        // 有画面的分镜才生成片段；转场时每个片段多渲染 overlap 秒，与下一片段叠化
+        let visuals: Vec<(&Scene, Visual)> = scenes
+            .iter()
+            .filter_map(|scene| visual_of(scene).map(|visual| (scene, visual)))
+            .collect();
+        let durations: Vec<f64> = visuals.iter().map(|(scene, _)| scene.duration).collect();
+        let overlap = self.transition.overlap(self.transition_duration, &durations);
 
         // 为每个场景创建带字幕的视频片段
         let mut segment_paths = Vec::new();
 
-        for scene in scenes {
-            if let Some(image_path) = &scene.image_path {
-                let segment_path = format!("{}/segment_{}.mp4", self.output_dir, scene.index);
-                
-                // 使用FFmpeg创建视频片段：图片 + 字幕
-                self.create_video_segment(
-                    image_path,
-                    &scene.subtitle,
-                    scene.duration,
-                    &segment_path,
-                )
-                .await?;
+        for (position, (scene, visual)) in visuals.iter().enumerate() {
+            let segment_path = format!("{}/segment_{}.mp4", self.output_dir, scene.index);
+            let tail = if position + 1 < visuals.len() { overlap } else { 0.0 };
 
-                // 转换为绝对路径
-                let abs_segment_path = PathBuf::from(&segment_path)
-                    .canonicalize()
-                    .map_err(|e| VideoError::VideoGenerationError(format!("Failed to get absolute path: {}", e)))?;
-                
-                concat_content.push_str(&format!("file '{}'\n", abs_segment_path.display()));
-                segment_paths.push(segment_path);
-            }
+            // 使用FFmpeg创建视频片段：图片或视频素材 + 字幕
+            self.create_video_segment(*visual, &scene.lines, scene.duration, tail, &segment_path)
+                .await?;
+            segment_paths.push(segment_path);
+            self.report(SEGMENTS_PERCENT * (position + 1) as f64 / visuals.len() as f64);
         }
 
-        // 写入concat文件
-        tokio::fs::write(&concat_file, concat_content).await?;
-
-        // 合并所有视频片段
+        // 合并所有视频片段：直接切换时无损拼接，否则用 xfade 重新编码
         let merged_video = format!("{}/merged.mp4", self.output_dir);
-        self.concat_videos(&concat_file, &merged_video).await?;
+        match self.transition.xfade_graph(overlap, &durations) {
+            Some(graph) => {
+                self.crossfade_videos(&segment_paths, &graph, &merged_video)
+                    .await?
+            }
+            None => self.concat_videos(&segment_paths, &merged_video).await?,
+        }
+        self.report(SEGMENTS_PERCENT + 5.0);
 
-        // 添加音频
-        self.add_audio(&merged_video, audio_path, output_path)
+        // 混音、响度标准化后添加音频
+        let (final_audio, loudness) = self.prepare_audio(audio_path, scenes).await?;
+        self.add_audio(&merged_video, &final_audio, output_path)
             .await?;
+        self.report(100.0);
+
+        // 校验最终成片
+        let info = media::probe(output_path).await?;
+        if info.video.is_none() || info.audio.is_none() {
+            return Err(VideoError::VideoGeneration(format!(
+                "Output {} is missing a video or audio stream",
+                output_path
+            )));
+        }
 
-        info!("Video generation completed: {}", output_path);
+        info!(
+            "Video generation completed: {} ({:.2}s)",
+            output_path,
+            info.duration.unwrap_or_default()
+        );
 
         // 清理临时文件
-        tokio::fs::remove_file(&concat_file).await.ok();
         tokio::fs::remove_file(&merged_video).await.ok();
+        if final_audio != audio_path {
+            tokio::fs::remove_file(&final_audio).await.ok();
+        }
         for segment in segment_paths {
             tokio::fs::remove_file(&segment).await.ok();
         }
 
-        Ok(())
+        Ok(RenderSummary {
+            duration: info.duration.unwrap_or_default(),
+            loudness,
+        })
     }
 
+    /// 渲染分镜片段；`tail` 为转场叠化多出的画面时长，字幕只显示到分镜结束
     async fn create_video_segment(
         &self,
-        image_path: &str,
-        subtitle: &str,
+        visual: Visual<'_>,
+        lines: &[Line],
+        duration: f64,
+        tail: f64,
+        output_path: &str,
+    ) -> Result<()> {
+        info!("Creating video segment for: {}", visual.path());
+
+        if self.subtitles.style == SubtitleStyle::Karaoke && !lines.is_empty() {
+            return self
+                .create_karaoke_segment(visual, lines, duration, tail, output_path)
+                .await;
+        }
+
+        // 每句台词从其语音开始显示到下一句开始，说话人按 cast 着色并加标签
+        let mut filters = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let text = match &line.label {
+                Some(label) => format!("{}：{}", label, line.text),
+                None => line.text.clone(),
+            };
+            // 最后一句一直显示到片段结束
+            let start = line.start;
+            let end = lines.get(i + 1).map_or(duration, |next| next.start);
+            filters.push(format!(
+                "drawtext=text='{}':fontfile={}:fontsize={}:fontcolor={}:x=(w-text_w)/2:y=h-100:box=1:boxcolor=black@0.5:boxborderw=10:enable='between(t,{:.3},{:.3})'",
+                escape_drawtext(&text),
+                self.subtitles.font_file,
+                self.subtitles.font_size,
+                line.color.as_deref().unwrap_or("white"),
+                start,
+                end
+            ));
+        }
+        if filters.is_empty() {
+            filters.push("null".to_string());
+        }
+
+        self.render(visual, &filters.join(","), duration + tail, output_path)
+            .await
+    }
+
+    /// 用 ASS 卡拉 OK 字幕渲染片段，字幕脚本写在片段旁边，渲染后删除
+    async fn create_karaoke_segment(
+        &self,
+        visual: Visual<'_>,
+        lines: &[Line],
         duration: f64,
+        tail: f64,
         output_path: &str,
     ) -> Result<()> {
-        info!("Creating video segment for: {}", subtitle);
+        let script = subtitle::karaoke_ass(lines, duration, self.canvas, &self.subtitles);
+        let ass_path = format!("{}.ass", output_path);
+        tokio::fs::write(&ass_path, script).await?;
 
-        // 转义字幕文本中的特殊字符
-        let escaped_subtitle = subtitle
-            .replace('\\', "\\\\")
-            .replace('\'', "'\\''")
-            .replace(':', "\\:")
-            .replace(',', "\\,");
+        let fonts_dir = Path::new(&self.subtitles.font_file)
+            .parent()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let filter = format!(
+            "ass=filename='{}':fontsdir='{}'",
+            escape_filter_path(&ass_path),
+            escape_filter_path(&fonts_dir)
+        );
+
+        let result = self
+            .render(visual, &filter, duration + tail, output_path)
+            .await;
+        tokio::fs::remove_file(&ass_path).await.ok();
+        result
+    }
+
+    /// 图片或视频素材缩放到画面尺寸、加滤镜，渲染为指定时长的无声片段
+    async fn render(
+        &self,
+        visual: Visual<'_>,
+        filter: &str,
+        duration: f64,
+        output_path: &str,
+    ) -> Result<()> {
+        let (width, height) = self.canvas;
+        let mut filters = vec![format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1",
+            w = width,
+            h = height
+        )];
+        match visual {
+            Visual::KenBurns(_) => {
+                // 先放大再推近，避免 zoompan 取整造成的抖动；每个输入帧输出一帧
+                let frames = (duration * self.fps as f64).max(1.0);
+                filters.push(format!("scale={}:{}", width * 2, height * 2));
+                filters.push(format!(
+                    "zoompan=z='1+{:.3}*on/{:.0}':x='iw/2-(iw/zoom/2)':y='ih/2-(ih/zoom/2)':d=1:s={}x{}:fps={}",
+                    KEN_BURNS_ZOOM, frames, width, height, self.fps
+                ));
+            }
+            Visual::Clip { looped: false, .. } => {
+                filters.push(format!("tpad=stop_mode=clone:stop_duration={:.3}", duration));
+            }
+            _ => {}
+        }
+        filters.push(filter.to_string());
+        let filter = filters.join(",");
+
+        // 图片用 -loop 1 循环，视频从入点开始（需要时用 -stream_loop -1 循环），再由 -t 裁到分镜时长
+        let input: Vec<String> = match visual {
+            Visual::Still(path) => vec!["-loop".into(), "1".into(), "-i".into(), path.into()],
+            Visual::KenBurns(path) => vec![
+                "-loop".into(),
+                "1".into(),
+                "-framerate".into(),
+                self.fps.to_string(),
+                "-i".into(),
+                path.into(),
+            ],
+            Visual::Clip {
+                path,
+                start,
+                looped,
+            } => {
+                let mut input = Vec::new();
+                if looped {
+                    input.extend(["-stream_loop".to_string(), "-1".to_string()]);
+                }
+                input.extend([
+                    "-ss".to_string(),
+                    format!("{:.3}", start),
+                    "-i".to_string(),
+                    path.to_string(),
+                ]);
+                input
+            }
+        };
 
         // 使用FFmpeg创建带字幕的视频片段
-        // -loop 1: 循环图片
-        // -i: 输入图片
-        // -vf: 视频过滤器，添加字幕
+        // -vf: 视频过滤器，缩放并添加字幕
         // -t: 持续时间
+        // -an: 素材原声另行混音，片段不带音频
         // -pix_fmt yuv420p: 像素格式，确保兼容性
         let output = Command::new("ffmpeg")
+            .arg("-y")
+            .args(&input)
             .args([
-                "-y",
-                "-loop",
-                "1",
-                "-i",
-                image_path,
                 "-vf",
-                &format!(
-                    "drawtext=text='{}':fontfile=/System/Library/Fonts/PingFang.ttc:fontsize=48:fontcolor=white:x=(w-text_w)/2:y=h-100:box=1:boxcolor=black@0.5:boxborderw=10",
-                    escaped_subtitle
-                ),
+                &filter,
                 "-t",
                 &duration.to_string(),
+                "-an",
                 "-pix_fmt",
                 "yuv420p",
                 "-r",
-                "30",
+                &self.fps.to_string(),
                 output_path,
             ])
+            .kill_on_drop(true)
             .output()
-            .map_err(|e| VideoError::VideoGenerationError(format!("Failed to run FFmpeg: {}", e)))?;
+            .await
+            .map_err(|e| VideoError::VideoGeneration(format!("Failed to run FFmpeg: {}", e)))?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoError::VideoGenerationError(format!(
+            return Err(VideoError::VideoGeneration(format!(
                 "FFmpeg segment creation failed: {}",
                 error
             )));
@@ -132,9 +390,20 @@ impl VideoGenerator {
         Ok(())
     }
 
-    async fn concat_videos(&self, concat_file: &str, output_path: &str) -> Result<()> {
+    async fn concat_videos(&self, segments: &[String], output_path: &str) -> Result<()> {
         info!("Concatenating video segments...");
 
+        // 写入 concat 列表，使用绝对路径
+        let concat_file = format!("{}/concat.txt", self.output_dir);
+        let mut concat_content = String::new();
+        for segment in segments {
+            let abs_segment_path = PathBuf::from(segment)
+                .canonicalize()
+                .map_err(|e| VideoError::VideoGeneration(format!("Failed to get absolute path: {}", e)))?;
+            concat_content.push_str(&format!("file '{}'\n", abs_segment_path.display()));
+        }
+        tokio::fs::write(&concat_file, concat_content).await?;
+
         let output = Command::new("ffmpeg")
             .args([
                 "-y",
@@ -143,17 +412,21 @@ impl VideoGenerator {
                 "-safe",
                 "0",
                 "-i",
-                concat_file,
+                &concat_file,
                 "-c",
                 "copy",
                 output_path,
             ])
+            .kill_on_drop(true)
             .output()
-            .map_err(|e| VideoError::VideoGenerationError(format!("Failed to run FFmpeg: {}", e)))?;
+            .await
+            .map_err(|e| VideoError::VideoGeneration(format!("Failed to run FFmpeg: {}", e)));
+        tokio::fs::remove_file(&concat_file).await.ok();
+        let output = output?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoError::VideoGenerationError(format!(
+            return Err(VideoError::VideoGeneration(format!(
                 "FFmpeg concat failed: {}",
                 error
             )));
@@ -163,9 +436,92 @@ impl VideoGenerator {
         Ok(())
     }
 
+    /// 按 xfade 滤镜图叠化相邻片段并重新编码
+    async fn crossfade_videos(
+        &self,
+        segments: &[String],
+        graph: &str,
+        output_path: &str,
+    ) -> Result<()> {
+        info!(
+            "Joining {} segments with {:?} transitions...",
+            segments.len(),
+            self.transition
+        );
+
+        let mut command = Command::new("ffmpeg");
+        command.arg("-y");
+        for segment in segments {
+            command.args(["-i", segment]);
+        }
+        let output = command
+            .args([
+                "-filter_complex",
+                graph,
+                "-map",
+                "[vout]",
+                "-pix_fmt",
+                "yuv420p",
+                "-r",
+                &self.fps.to_string(),
+                output_path,
+            ])
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| VideoError::VideoGeneration(format!("Failed to run FFmpeg: {}", e)))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(VideoError::VideoGeneration(format!(
+                "FFmpeg transition failed: {}",
+                error
+            )));
+        }
+
+        info!("Joined video: {}", output_path);
+        Ok(())
+    }
+
+    /// 准备最终混音：混入背景音乐与音效、响度标准化，返回用于封装的音频路径
+    async fn prepare_audio(
+        &self,
+        audio_path: &str,
+        scenes: &[Scene],
+    ) -> Result<(String, Option<LoudnessReport>)> {
+        let mut current = audio_path.to_string();
+
+        if self.music.is_some() || has_sound_cues(scenes) {
+            let mix_path = format!("{}/mix.wav", self.output_dir);
+            self.mix_layers(audio_path, scenes, &mix_path).await?;
+            current = mix_path;
+        }
+
+        let mut report = None;
+        if let Some(target) = self.loudness {
+            let normalized_path = format!("{}/mix_normalized.wav", self.output_dir);
+            report = Some(media::normalize_loudness(&current, target, &normalized_path).await?);
+            if current != audio_path {
+                tokio::fs::remove_file(&current).await.ok();
+            }
+            current = normalized_path;
+        }
+
+        Ok((current, report))
+    }
+
     async fn add_audio(&self, video_path: &str, audio_path: &str, output_path: &str) -> Result<()> {
         info!("Adding audio to video...");
 
+        // MP4 可直接封装的有损编码（AAC/MP3/Opus）不再转码，避免二次有损压缩；
+        // 无损旁白只编码一次 AAC
+        let audio_info = media::probe(audio_path).await?;
+        let audio_codec: &[&str] = if media::mp4_compatible(&audio_info) {
+            &["-c:a", "copy"]
+        } else {
+            &["-c:a", "aac", "-b:a", "192k"]
+        };
+
         let output = Command::new("ffmpeg")
             .args([
                 "-y",
@@ -175,8 +531,9 @@ impl VideoGenerator {
                 audio_path,
                 "-c:v",
                 "copy",
-                "-c:a",
-                "aac",
+            ])
+            .args(audio_codec)
+            .args([
                 "-map",
                 "0:v:0",
                 "-map",
@@ -184,12 +541,14 @@ impl VideoGenerator {
                 "-shortest",
                 output_path,
             ])
+            .kill_on_drop(true)
             .output()
-            .map_err(|e| VideoError::VideoGenerationError(format!("Failed to run FFmpeg: {}", e)))?;
+            .await
+            .map_err(|e| VideoError::VideoGeneration(format!("Failed to run FFmpeg: {}", e)))?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoError::VideoGenerationError(format!(
+            return Err(VideoError::VideoGeneration(format!(
                 "FFmpeg audio merge failed: {}",
                 error
             )));
@@ -198,4 +557,144 @@ impl VideoGenerator {
         info!("Added audio to video: {}", output_path);
         Ok(())
     }
+
+    /// 混入背景音乐与音效：音乐循环/裁剪到时间线、淡入淡出并在旁白处闪避，
+    /// 音效按分镜位置铺在旁白之下，输出 PCM WAV
+    async fn mix_layers(
+        &self,
+        audio_path: &str,
+        scenes: &[Scene],
+        output_path: &str,
+    ) -> Result<()> {
+        let timeline: f64 = scenes.iter().map(|s| s.duration).sum();
+        let mut args: Vec<String> = vec!["-y".into(), "-i".into(), audio_path.to_string()];
+        let mut chains = Vec::new();
+        let mut labels = vec!["[0:a]".to_string()];
+
+        if let Some(music) = &self.music {
+            let music_path = mix::pick_music(&music.path, timeline).await?;
+            let intervals = mix::speech_intervals(scenes);
+            info!(
+                "Mixing background music {} under {} narration intervals",
+                music_path,
+                intervals.len()
+            );
+            args.extend(["-stream_loop", "-1", "-i"].map(String::from));
+            args.push(music_path);
+            chains.push(format!("[1:a]{}[music]", music.filter(&intervals, timeline)));
+            labels.push("[music]".to_string());
+        }
+
+        let mut start = 0.0;
+        let mut cues = 0;
+        for scene in scenes {
+            for cue in &scene.sounds {
+                let Some(path) = &cue.resolved_path else {
+                    continue;
+                };
+                let input = labels.len();
+                if cue.looped {
+                    args.extend(["-stream_loop", "-1"].map(String::from));
+                }
+                args.extend(["-i".to_string(), path.clone()]);
+                chains.push(format!(
+                    "[{}:a]{}[sfx{}]",
+                    input,
+                    cue.filter(start, scene.duration, timeline),
+                    input
+                ));
+                labels.push(format!("[sfx{}]", input));
+                cues += 1;
+            }
+            start += scene.duration;
+        }
+        if cues > 0 {
+            info!("Mixing {} sound cues", cues);
+        }
+
+        // 保留原声的视频素材按分镜位置混入，与画面同一入点并同样循环
+        let mut start = 0.0;
+        for scene in scenes {
+            if let Some(footage) = &scene.media {
+                if let Some(path) = footage.kept_audio() {
+                    let input = labels.len();
+                    if footage.looped {
+                        args.extend(["-stream_loop", "-1"].map(String::from));
+                    }
+                    args.push("-ss".to_string());
+                    args.push(format!("{:.3}", footage.start.max(0.0)));
+                    args.extend(["-i".to_string(), path.to_string()]);
+                    let cue = SoundCue {
+                        file: None,
+                        tag: None,
+                        offset: 0.0,
+                        volume: footage.volume,
+                        looped: true,
+                        resolved_path: None,
+                    };
+                    chains.push(format!(
+                        "[{}:a]{}[clip{}]",
+                        input,
+                        cue.filter(start, scene.duration, timeline),
+                        input
+                    ));
+                    labels.push(format!("[clip{}]", input));
+                }
+            }
+            start += scene.duration;
+        }
+
+        let filter_complex = format!(
+            "{};{}amix=inputs={}:duration=first:dropout_transition=0:normalize=0[aout]",
+            chains.join(";"),
+            labels.concat(),
+            labels.len()
+        );
+        args.extend(
+            ["-filter_complex", &filter_complex, "-map", "[aout]", "-c:a", "pcm_s16le", output_path]
+                .map(String::from),
+        );
+
+        let output = Command::new("ffmpeg")
+            .args(&args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| VideoError::VideoGeneration(format!("Failed to run FFmpeg: {}", e)))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(VideoError::VideoGeneration(format!(
+                "FFmpeg audio mix failed: {}",
+                error
+            )));
+        }
+
+        info!("Mixed narration, music and sound effects: {}", output_path);
+        Ok(())
+    }
+}
+
+/// 是否有可混入的音效或素材原声
+pub fn has_sound_cues(scenes: &[Scene]) -> bool {
+    scenes.iter().any(|scene| {
+        scene.sounds.iter().any(|cue| cue.resolved_path.is_some())
+            || scene
+                .media
+                .as_ref()
+                .is_some_and(|footage| footage.kept_audio().is_some())
+    })
+}
+
+/// 转义滤镜参数中单引号包裹的路径
+fn escape_filter_path(path: &str) -> String {
+    path.replace('\\', "/").replace('\'', "'\\''")
+}
+
+/// 转义 drawtext 字幕文本中的特殊字符
+fn escape_drawtext(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "'\\''")
+        .replace(':', "\\:")
+        .replace(',', "\\,")
 }
@@ -0,0 +1,290 @@
+use crate::error::{Result, VideoError};
+use crate::media;
+use crate::scene::{Scene, SoundCue};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// 背景音乐及闪避（ducking）参数
+#[derive(Debug, Clone)]
+pub struct MusicOptions {
+    /// 音乐文件，或从中挑选曲目的目录
+    pub path: String,
+    /// 音乐整体音量（dB）
+    pub volume_db: f64,
+    /// 旁白播放时音乐额外压低的幅度（dB，负数）
+    pub duck_db: f64,
+    /// 淡入淡出时长（秒）
+    pub fade: f64,
+}
+
+/// 闪避音量的渐变时长（秒）
+const DUCK_RAMP: f64 = 0.3;
+
+/// 循环环境声首尾的淡入淡出时长（秒）
+const CUE_FADE: f64 = 0.5;
+
+/// 根据各句台词时长计算旁白播放的时间段
+pub fn speech_intervals(scenes: &[Scene]) -> Vec<(f64, f64)> {
+    let mut intervals: Vec<(f64, f64)> = Vec::new();
+    let mut offset = 0.0;
+
+    for scene in scenes {
+        for line in &scene.lines {
+            let start = offset + line.start;
+            let end = start + line.duration;
+
+            // 间隔小于两段渐变时间的相邻片段合并，避免音乐忽高忽低
+            match intervals.last_mut() {
+                Some(last) if start - last.1 < DUCK_RAMP * 2.0 => last.1 = end,
+                _ => intervals.push((start, end)),
+            }
+        }
+        offset += scene.duration;
+    }
+
+    intervals
+}
+
+/// 选择背景音乐：目录中优先取能覆盖整条时间线的最短曲目，否则取最长的循环播放
+pub async fn pick_music(path: &str, timeline: f64) -> Result<String> {
+    if !Path::new(path).is_dir() {
+        return Ok(path.to_string());
+    }
+
+    let mut tracks = Vec::new();
+    let mut entries = tokio::fs::read_dir(path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let file = entry.path().to_string_lossy().into_owned();
+        if let Ok(info) = media::probe(&file).await {
+            if let (Some(_), Some(duration)) = (&info.audio, info.duration) {
+                tracks.push((file, duration));
+            }
+        }
+    }
+    tracks.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let covering = tracks
+        .iter()
+        .filter(|(_, duration)| *duration >= timeline)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    let longest = tracks.iter().max_by(|a, b| a.1.total_cmp(&b.1));
+
+    let (track, duration) = covering
+        .or(longest)
+        .ok_or_else(|| VideoError::Media(format!("No playable music found in {}", path)))?;
+    info!("Picked background music {} ({:.2}s)", track, duration);
+    Ok(track.clone())
+}
+
+impl MusicOptions {
+    /// 构造背景音乐的滤镜链：裁剪/循环到时间线、音量、闪避、淡入淡出
+    pub fn filter(&self, intervals: &[(f64, f64)], timeline: f64) -> String {
+        let fade = self.fade.min(timeline / 2.0).max(0.0);
+        let mut filters = vec![
+            format!("atrim=0:{:.3}", timeline),
+            "asetpts=N/SR/TB".to_string(),
+            format!("volume={:.2}dB", self.volume_db),
+        ];
+
+        if !intervals.is_empty() {
+            filters.push(format!(
+                "volume='{}':eval=frame",
+                duck_expression(intervals, self.duck_db)
+            ));
+        }
+        if fade > 0.0 {
+            filters.push(format!("afade=t=in:st=0:d={:.3}", fade));
+            filters.push(format!(
+                "afade=t=out:st={:.3}:d={:.3}",
+                timeline - fade,
+                fade
+            ));
+        }
+
+        filters.join(",")
+    }
+}
+
+/// 音量自动化表达式：旁白时间段内压低到 duck_db，前后各有线性渐变
+fn duck_expression(intervals: &[(f64, f64)], duck_db: f64) -> String {
+    let gain = 10f64.powf(duck_db.min(0.0) / 20.0);
+
+    let ramps: Vec<String> = intervals
+        .iter()
+        .map(|(start, end)| {
+            format!(
+                "clip((t-{:.3})/{r},0,1)*clip(({:.3}-t)/{r},0,1)",
+                start - DUCK_RAMP,
+                end + DUCK_RAMP,
+                r = DUCK_RAMP
+            )
+        })
+        .collect();
+
+    // FFmpeg 的 max 只接受两个参数，逐层嵌套
+    let envelope = ramps
+        .into_iter()
+        .reduce(|acc, ramp| format!("max({},{})", acc, ramp))
+        .unwrap_or_else(|| "0".to_string());
+
+    format!("1-{:.4}*{}", 1.0 - gain, envelope)
+}
+
+/// 列出音效库中可用的标签：音频文件名（不含扩展名）及子目录名
+pub async fn sfx_tags(dir: &str) -> Result<Vec<String>> {
+    let mut tags = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let tag = if path.is_dir() {
+            path.file_name()
+        } else {
+            path.file_stem()
+        };
+        if let Some(tag) = tag.and_then(|t| t.to_str()) {
+            if !tag.starts_with('.') && !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+    tags.sort();
+    Ok(tags)
+}
+
+/// 在素材目录中查找标签对应的文件：同名文件 → 同名子目录中的第一个文件 → 以标签开头的文件
+pub async fn find_tagged(dir: &str, tag: &str) -> Option<String> {
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        files.push(entry.path());
+    }
+    files.sort();
+
+    let stem_of = |path: &Path| {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_lowercase())
+            .unwrap_or_default()
+    };
+    let tag = tag.to_lowercase();
+
+    if let Some(file) = files.iter().find(|p| p.is_file() && stem_of(p) == tag) {
+        return Some(file.to_string_lossy().into_owned());
+    }
+    if let Some(sub) = files.iter().find(|p| {
+        p.is_dir()
+            && p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.to_lowercase() == tag)
+    }) {
+        let mut inner = Vec::new();
+        let mut entries = tokio::fs::read_dir(sub).await.ok()?;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().is_file() {
+                inner.push(entry.path());
+            }
+        }
+        inner.sort();
+        if let Some(file) = inner.first() {
+            return Some(file.to_string_lossy().into_owned());
+        }
+    }
+    files
+        .iter()
+        .find(|p| p.is_file() && stem_of(p).starts_with(&tag))
+        .map(|p| p.to_string_lossy().into_owned())
+}
+
+/// 为分镜中的音效解析实际文件并校验，找不到或无法解码的音效会被跳过
+///
+/// 音效文件属于用户的素材目录，只读校验，不修正扩展名。
+pub async fn resolve_cues(scenes: &mut [Scene], sfx_dir: Option<&str>) {
+    for scene in scenes.iter_mut() {
+        for cue in scene.sounds.iter_mut() {
+            let path = match (&cue.file, &cue.tag, sfx_dir) {
+                (Some(file), _, _) => Some(file.clone()),
+                (None, Some(tag), Some(dir)) => find_tagged(dir, tag).await,
+                _ => None,
+            };
+            let Some(path) = path else {
+                warn!(
+                    "Scene {}: no sound file for cue {:?}, skipping",
+                    scene.index,
+                    cue.tag.as_deref().unwrap_or("")
+                );
+                continue;
+            };
+            match media::probe(&path)
+                .await
+                .and_then(|info| info.require_audio(&path))
+            {
+                Ok(_) => cue.resolved_path = Some(path),
+                Err(e) => warn!("Scene {}: unusable sound {}: {}", scene.index, path, e),
+            }
+        }
+    }
+}
+
+impl SoundCue {
+    /// 构造音效的滤镜链：裁剪、音量、定位到时间线
+    ///
+    /// `start` 为所在分镜在时间线上的起点；循环音效铺满分镜剩余时长，
+    /// 单次音效最多延续到时间线结束。
+    pub fn filter(&self, start: f64, scene_duration: f64, timeline: f64) -> String {
+        let offset = self.offset.clamp(0.0, scene_duration);
+        let length = if self.looped {
+            scene_duration - offset
+        } else {
+            timeline - start - offset
+        }
+        .max(0.0);
+
+        let mut filters = vec![
+            format!("atrim=0:{:.3}", length),
+            "asetpts=N/SR/TB".to_string(),
+            format!("volume={:.2}dB", self.volume),
+        ];
+        if self.looped {
+            let fade = CUE_FADE.min(length / 2.0);
+            if fade > 0.0 {
+                filters.push(format!("afade=t=in:st=0:d={:.3}", fade));
+                filters.push(format!(
+                    "afade=t=out:st={:.3}:d={:.3}",
+                    length - fade,
+                    fade
+                ));
+            }
+        }
+        let delay = ((start + offset) * 1000.0).round() as u64;
+        filters.push(format!("adelay={}:all=1", delay));
+
+        filters.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duck_expression_ramps_around_each_interval() {
+        assert_eq!(
+            duck_expression(&[(1.0, 2.0)], -6.0),
+            "1-0.4988*clip((t-0.700)/0.3,0,1)*clip((2.300-t)/0.3,0,1)"
+        );
+    }
+
+    #[test]
+    fn duck_expression_nests_max_for_several_intervals() {
+        let expression = duck_expression(&[(1.0, 2.0), (4.0, 5.0), (8.0, 9.0)], -20.0);
+        assert!(expression.starts_with("1-0.9000*max(max(clip((t-0.700)"));
+        assert_eq!(expression.matches("max(").count(), 2);
+        assert!(expression.ends_with("clip((9.300-t)/0.3,0,1))"));
+    }
+
+    #[test]
+    fn duck_expression_without_speech_or_with_positive_gain() {
+        assert_eq!(duck_expression(&[], -6.0), "1-0.4988*0");
+        assert!(duck_expression(&[(1.0, 2.0)], 6.0).starts_with("1-0.0000*"));
+    }
+}
@@ -1,3 +1,10 @@
+pub mod footage;
 pub mod generator;
+pub mod mix;
+pub mod subtitle;
+pub mod transition;
 
-pub use generator::VideoGenerator;
+pub use generator::{has_sound_cues, RenderSummary, VideoGenerator};
+pub use mix::MusicOptions;
+pub use subtitle::{SubtitleOptions, SubtitleStyle};
+pub use transition::Transition;
@@ -0,0 +1,157 @@
+use crate::scene::Line;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// 字幕样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleStyle {
+    /// 整句显示
+    Plain,
+    /// 卡拉 OK 式逐字高亮（ASS \kf）
+    Karaoke,
+}
+
+/// 字幕外观
+#[derive(Debug, Clone)]
+pub struct SubtitleOptions {
+    pub style: SubtitleStyle,
+    /// 卡拉 OK 样式下已读到的字的颜色
+    pub highlight_color: String,
+    /// drawtext 使用的字体文件
+    pub font_file: String,
+    /// ASS 字幕按名称引用的字体，应与 font_file 为同一字体
+    pub font_name: String,
+    pub font_size: u32,
+}
+
+impl Default for SubtitleOptions {
+    fn default() -> Self {
+        Self {
+            style: SubtitleStyle::Plain,
+            highlight_color: "#FFD700".to_string(),
+            font_file: "/System/Library/Fonts/PingFang.ttc".to_string(),
+            font_name: "PingFang SC".to_string(),
+            font_size: 48,
+        }
+    }
+}
+
+/// 把 `#RRGGBB` 或常用颜色名转换为 ASS 的 `&HAABBGGRR` 格式
+fn ass_color(color: &str, alpha: u8) -> String {
+    let rgb = match color.trim().to_ascii_lowercase().as_str() {
+        hex if hex.len() == 7 && hex.starts_with('#') => {
+            u32::from_str_radix(&hex[1..], 16).unwrap_or(0xFFFFFF)
+        }
+        "black" => 0x000000,
+        "red" => 0xFF0000,
+        "green" => 0x00FF00,
+        "blue" => 0x0000FF,
+        "yellow" => 0xFFFF00,
+        "cyan" => 0x00FFFF,
+        "magenta" => 0xFF00FF,
+        _ => 0xFFFFFF,
+    };
+    let (r, g, b) = ((rgb >> 16) & 0xFF, (rgb >> 8) & 0xFF, rgb & 0xFF);
+    format!("&H{:02X}{:02X}{:02X}{:02X}", alpha, b, g, r)
+}
+
+/// ASS 时间格式 H:MM:SS.cc
+fn ass_time(seconds: f64) -> String {
+    let cs = (seconds.max(0.0) * 100.0).round() as u64;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        cs / 360_000,
+        cs / 6000 % 60,
+        cs / 100 % 60,
+        cs % 100
+    )
+}
+
+/// ASS 文本中花括号表示覆盖标签，换行需写成 \N
+fn escape_ass(text: &str) -> String {
+    text.replace('\\', "＼")
+        .replace('{', "｛")
+        .replace('}', "｝")
+        .replace('\n', "\\N")
+}
+
+/// 生成一个分镜片段的卡拉 OK 字幕脚本
+///
+/// 未读到的字使用台词颜色，读到的字渐变为 `highlight`；
+/// 每句从其语音开始显示到下一句开始，与 drawtext 字幕的时间窗一致。
+pub fn karaoke_ass(
+    lines: &[Line],
+    duration: f64,
+    (width, height): (u32, u32),
+    options: &SubtitleOptions,
+) -> String {
+    let highlight = options.highlight_color.as_str();
+    let mut script = format!(
+        "[Script Info]\n\
+         ScriptType: v4.00+\n\
+         PlayResX: {width}\n\
+         PlayResY: {height}\n\
+         WrapStyle: 0\n\
+         \n\
+         [V4+ Styles]\n\
+         Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+         Style: Default,{font},{size},{primary},&H00FFFFFF,&H80000000,&H80000000,0,0,0,0,100,100,0,0,3,10,0,2,40,40,60,1\n\
+         \n\
+         [Events]\n\
+         Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        font = options.font_name,
+        size = options.font_size,
+        primary = ass_color(highlight, 0),
+    );
+
+    for (i, line) in lines.iter().enumerate() {
+        let end = lines.get(i + 1).map_or(duration, |next| next.start);
+        let base = ass_color(line.color.as_deref().unwrap_or("white"), 0);
+
+        // 标签不参与高亮，保持台词颜色
+        let mut text = format!("{{\\2c{}}}", base);
+        if let Some(label) = &line.label {
+            text.push_str(&format!(
+                "{{\\1c{}}}{}：{{\\1c{}}}",
+                base,
+                escape_ass(label),
+                ass_color(highlight, 0)
+            ));
+        }
+
+        if line.words.is_empty() {
+            text.push_str(&format!(
+                "{{\\kf{}}}{}",
+                (line.duration * 100.0).round() as u64,
+                escape_ass(&line.text)
+            ));
+        } else {
+            // \k 时长是相对的，字与字之间的停顿用空的 \k 补齐
+            let mut t = 0.0;
+            for word in &line.words {
+                if word.start > t {
+                    text.push_str(&format!(
+                        "{{\\k{}}}",
+                        ((word.start - t) * 100.0).round() as u64
+                    ));
+                }
+                text.push_str(&format!(
+                    "{{\\kf{}}}{}",
+                    ((word.end - word.start) * 100.0).round() as u64,
+                    escape_ass(&word.text)
+                ));
+                t = word.end;
+            }
+        }
+
+        script.push_str(&format!(
+            "Dialogue: 0,{},{},Default,,0,0,0,,{}\n",
+            ass_time(line.start),
+            ass_time(end),
+            text
+        ));
+    }
+
+    script
+}
@@ -0,0 +1,97 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// 分镜之间的转场效果
+///
+/// 除直接切换外，相邻片段用 FFmpeg xfade 叠化过渡：前一片段多渲染一段转场时长的画面，
+/// 与后一片段开头重叠，每个分镜仍从其旁白开始的时刻出现，音画保持对齐。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transition {
+    /// 直接切换
+    Cut,
+    /// 经黑场淡出淡入
+    Fade,
+    /// 经白场闪切
+    Flash,
+}
+
+impl Transition {
+    /// 对应的 xfade 转场名，直接切换时返回 None
+    pub fn xfade(self) -> Option<&'static str> {
+        match self {
+            Transition::Cut => None,
+            Transition::Fade => Some("fadeblack"),
+            Transition::Flash => Some("fadewhite"),
+        }
+    }
+
+    /// 实际使用的转场时长：不超过最短分镜的一半，直接切换或不足两个片段时为 0
+    pub fn overlap(self, transition_duration: f64, durations: &[f64]) -> f64 {
+        if self.xfade().is_none() || durations.len() < 2 {
+            return 0.0;
+        }
+        let shortest = durations.iter().copied().fold(f64::INFINITY, f64::min);
+        transition_duration.min(shortest / 2.0).max(0.0)
+    }
+
+    /// 串联各片段的 xfade 滤镜图，输出标签为 `[vout]`
+    ///
+    /// 除最后一个片段外，每个片段都比分镜长 `overlap` 秒；第 k 次转场从第 k 个分镜的起点开始，
+    /// 即此前各分镜时长之和，因此合成后的总时长仍等于各分镜时长之和。
+    pub fn xfade_graph(self, overlap: f64, durations: &[f64]) -> Option<String> {
+        let name = self.xfade()?;
+        if overlap <= 0.0 || durations.len() < 2 {
+            return None;
+        }
+
+        // 统一时间基，xfade 要求两路输入一致
+        let mut graph: Vec<String> = (0..durations.len())
+            .map(|i| format!("[{}:v]settb=AVTB[s{}]", i, i))
+            .collect();
+        let mut previous = "s0".to_string();
+        let mut offset = 0.0;
+        for (i, duration) in durations[..durations.len() - 1].iter().enumerate() {
+            let next = i + 1;
+            offset += duration;
+            let label = if next + 1 == durations.len() {
+                "vout".to_string()
+            } else {
+                format!("x{}", next)
+            };
+            graph.push(format!(
+                "[{}][s{}]xfade=transition={}:duration={:.3}:offset={:.3}[{}]",
+                previous, next, name, overlap, offset, label
+            ));
+            previous = label;
+        }
+        Some(graph.join(";"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap_is_limited_by_the_shortest_scene() {
+        assert_eq!(Transition::Fade.overlap(0.5, &[4.0, 3.0]), 0.5);
+        assert_eq!(Transition::Fade.overlap(2.0, &[4.0, 1.0]), 0.5);
+        assert_eq!(Transition::Cut.overlap(0.5, &[4.0, 3.0]), 0.0);
+        assert_eq!(Transition::Flash.overlap(0.5, &[4.0]), 0.0);
+    }
+
+    #[test]
+    fn xfade_offsets_follow_scene_starts() {
+        let graph = Transition::Flash
+            .xfade_graph(0.5, &[2.0, 3.0, 4.0])
+            .unwrap();
+        assert!(
+            graph.contains("[s0][s1]xfade=transition=fadewhite:duration=0.500:offset=2.000[x1]")
+        );
+        assert!(
+            graph.contains("[x1][s2]xfade=transition=fadewhite:duration=0.500:offset=5.000[vout]")
+        );
+        assert!(Transition::Cut.xfade_graph(0.5, &[2.0, 3.0]).is_none());
+    }
+}